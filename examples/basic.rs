@@ -1,5 +1,5 @@
 use std::env;
-use teslatte::auth::AccessToken;
+use teslatte::auth::{AccessToken, AuthConfig};
 use teslatte::products::Product;
 use teslatte::OwnerApi;
 
@@ -8,9 +8,11 @@ async fn main() {
     tracing_subscriber::fmt::init();
 
     let api = match env::var("TESLA_ACCESS_TOKEN") {
-        Ok(t) => OwnerApi::new(AccessToken(t), None),
+        Ok(t) => OwnerApi::new(AccessToken::new(t), None),
         Err(_) => {
-            let api = OwnerApi::from_interactive_url().await.unwrap();
+            let api = OwnerApi::from_interactive_url(&AuthConfig::default())
+                .await
+                .unwrap();
             println!("TOKEN: {:?}", api.access_token);
             api
         }
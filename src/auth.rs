@@ -1,21 +1,221 @@
 use crate::error::TeslatteError::{CouldNotFindCallbackCode, CouldNotFindState};
+use crate::redact::{self, redacted_fmt};
+use crate::scopes::Scope;
 use crate::{OwnerApi, TeslatteError};
-use derive_more::{Display, FromStr};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, TimeDelta, Utc};
+use derive_more::FromStr;
 use rand::Rng;
 use reqwest::Client;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::io::{stdin, stdout, Write};
+use strum::{Display, EnumString};
 use url::Url;
 
-const AUTHORIZE_URL: &str = "https://auth.tesla.com/oauth2/v3/authorize";
-const TOKEN_URL: &str = "https://auth.tesla.com/oauth2/v3/token";
+/// Which Tesla account servers to authenticate with.
+///
+/// Accounts in mainland China are separate from the rest of the world, and use `.cn` hosts for
+/// both authentication and the API.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum AuthRegion {
+    #[default]
+    Global,
+    China,
+}
+
+impl AuthRegion {
+    fn auth_host(&self) -> &'static str {
+        match self {
+            AuthRegion::Global => "https://auth.tesla.com",
+            AuthRegion::China => "https://auth.tesla.cn",
+        }
+    }
+
+    fn api_url(&self) -> &'static str {
+        match self {
+            AuthRegion::Global => "https://owner-api.teslamotors.com/api/1",
+            AuthRegion::China => "https://owner-api.vn.cloud.tesla.cn/api/1",
+        }
+    }
+}
+
+/// URLs used to log in, refresh tokens, and call the API.
+///
+/// Use [AuthConfig::new()] with an [AuthRegion] for the known Tesla servers, or change the fields
+/// to point somewhere else.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthConfig {
+    pub authorize_url: String,
+    pub token_url: String,
+    pub redirect_uri: String,
+    /// Base URL of the API, including `/api/1`.
+    pub api_url: String,
+}
+
+impl AuthConfig {
+    pub fn new(region: AuthRegion) -> Self {
+        let auth_host = region.auth_host();
+        Self {
+            authorize_url: format!("{auth_host}/oauth2/v3/authorize"),
+            token_url: format!("{auth_host}/oauth2/v3/token"),
+            redirect_uri: format!("{auth_host}/void/callback"),
+            api_url: region.api_url().to_string(),
+        }
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self::new(AuthRegion::Global)
+    }
+}
+
+impl From<AuthRegion> for AuthConfig {
+    fn from(region: AuthRegion) -> Self {
+        Self::new(region)
+    }
+}
+
+/// An OAuth access token.
+///
+/// `Debug` and `Display` don't show the token, so it doesn't end up in logs by accident. Use
+/// [AccessToken::expose()] when you really need it.
+#[derive(Clone, Serialize, Deserialize, FromStr)]
+pub struct AccessToken(String);
+
+impl AccessToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// Decode the claims of the access token.
+    ///
+    /// Tesla access tokens are JWTs. The signature is **not** verified, so only use this to
+    /// inspect your own tokens, e.g. to check the expiry or the scopes that were granted.
+    pub fn claims(&self) -> Result<AccessTokenClaims, TeslatteError> {
+        let payload = self
+            .0
+            .trim()
+            .split('.')
+            .nth(1)
+            .ok_or_else(|| TeslatteError::DecodeAccessTokenError("Not a JWT.".to_string()))?;
+
+        let json = URL_SAFE_NO_PAD
+            .decode(payload.trim_end_matches('='))
+            .map_err(|e| TeslatteError::DecodeAccessTokenError(e.to_string()))?;
+
+        serde_json::from_slice(&json)
+            .map_err(|e| TeslatteError::DecodeAccessTokenError(e.to_string()))
+    }
+}
+
+/// An OAuth refresh token. Like [AccessToken], it is redacted when printed.
+#[derive(Clone, Serialize, Deserialize, FromStr)]
+pub struct RefreshToken(String);
+
+impl RefreshToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromStr, Display)]
-pub struct AccessToken(pub String);
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromStr, Display)]
-pub struct RefreshToken(pub String);
+redacted_fmt!(AccessToken);
+redacted_fmt!(RefreshToken);
+
+/// The payload of an [AccessToken].
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccessTokenClaims {
+    /// Issuer, e.g. `https://auth.tesla.com/oauth2/v3/nts`.
+    pub iss: String,
+
+    /// The account the token belongs to.
+    pub sub: String,
+
+    /// The hosts this token can be used with. Tesla sends either a string or a list.
+    #[serde(default, deserialize_with = "string_or_vec")]
+    pub aud: Vec<String>,
+
+    /// Scopes granted to this token, e.g. `openid`, `vehicle_device_data`.
+    #[serde(default)]
+    pub scp: Vec<String>,
+
+    /// Expiry as a unix timestamp.
+    pub exp: i64,
+
+    /// Issued at as a unix timestamp.
+    pub iat: i64,
+
+    pub azp: Option<String>,
+    pub ou_code: Option<String>,
+    pub locale: Option<String>,
+}
+
+impl AccessTokenClaims {
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.exp, 0).unwrap_or_default()
+    }
+
+    pub fn issued_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.iat, 0).unwrap_or_default()
+    }
+
+    /// Time left until the token expires. Negative if it has already expired.
+    pub fn remaining(&self) -> TimeDelta {
+        self.expires_at() - Utc::now()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining() <= TimeDelta::zero()
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scp.iter().any(|s| s == scope)
+    }
+
+    /// Known scopes of the token. Unknown scopes like `email` are skipped.
+    pub fn scopes(&self) -> Vec<Scope> {
+        self.scp.iter().filter_map(|s| s.parse().ok()).collect()
+    }
+
+    /// The Fleet API host in the audience, if this token was issued for the Fleet API.
+    pub fn fleet_api_audience(&self) -> Option<&str> {
+        self.aud
+            .iter()
+            .map(|a| a.as_str())
+            .find(|a| a.contains("fleet-api"))
+    }
+}
+
+fn string_or_vec<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrVec {
+        String(String),
+        Vec(Vec<String>),
+    }
+
+    Ok(match StringOrVec::deserialize(deserializer)? {
+        StringOrVec::String(s) => vec![s],
+        StringOrVec::Vec(v) => v,
+    })
+}
 
 struct Callback {
     code: String,
@@ -26,16 +226,17 @@ impl OwnerApi {
     /// Show a URL for the user to click on to log into tesla.com, the ask them to paste the
     /// URL they end up on, which is a 404 page. The URL contains OAuth information needed to
     /// complete authentication for an access key.
-    pub async fn from_interactive_url() -> Result<OwnerApi, TeslatteError> {
-        let login_form = Self::get_login_url_for_user().await;
+    pub async fn from_interactive_url(auth_config: &AuthConfig) -> Result<OwnerApi, TeslatteError> {
+        let login_form = Self::get_login_url_for_user(auth_config).await;
         println!("{}", "-".repeat(80));
         println!("{}", login_form.url);
         println!("{}", "-".repeat(80));
         println!(
             r#"Visit the URL above, and log in to your Tesla account if not already logged in.
 After you log in (or already logged in), it will redirect you to a 404 error
-page, where the URL will start with https://auth.tesla.com/void/callback?code=...
-"#
+page, where the URL will start with {}?code=...
+"#,
+            auth_config.redirect_uri
         );
         let callback_url = ask_input("Enter the whole URL of the 404 page: ");
         println!(); // Newline to make the next output more separated and clear.
@@ -46,11 +247,16 @@ page, where the URL will start with https://auth.tesla.com/void/callback?code=..
     /// Generate a [LoginForm] containing a URL the user should visit.
     ///
     /// See [OwnerApi::from_callback_url()] for the next step.
-    pub async fn get_login_url_for_user() -> LoginForm {
+    pub async fn get_login_url_for_user(auth_config: &AuthConfig) -> LoginForm {
         let code = Code::new();
         let state = random_string(8);
-        let url = Self::login_url(&code, &state);
-        LoginForm { url, code, state }
+        let url = Self::login_url(auth_config, &code, &state);
+        LoginForm {
+            url,
+            code,
+            state,
+            auth_config: auth_config.clone(),
+        }
     }
 
     /// Parse a callback URL that the user was redirected to after logging in via
@@ -67,44 +273,55 @@ page, where the URL will start with https://auth.tesla.com/void/callback?code=..
             });
         }
 
-        let bearer = Self::exchange_auth_for_bearer(&login_form.code, &callback.code).await?;
+        let auth_config = &login_form.auth_config;
+        let bearer =
+            Self::exchange_auth_for_bearer(auth_config, &login_form.code, &callback.code).await?;
         let access_token = AccessToken(bearer.access_token);
         let refresh_token = RefreshToken(bearer.refresh_token);
-        Ok(OwnerApi::new(access_token, Some(refresh_token)))
+        Ok(OwnerApi::new_with_auth_config(
+            auth_config.clone(),
+            access_token,
+            Some(refresh_token),
+        ))
     }
 
     pub async fn from_refresh_token(
+        auth_config: &AuthConfig,
         refresh_token: &RefreshToken,
     ) -> Result<OwnerApi, TeslatteError> {
-        let response = Self::refresh_token(refresh_token).await?;
-        Ok(OwnerApi::new(
+        let response = Self::refresh_token(auth_config, refresh_token).await?;
+        Ok(OwnerApi::new_with_auth_config(
+            auth_config.clone(),
             response.access_token,
             Some(response.refresh_token),
         ))
     }
 
     async fn exchange_auth_for_bearer(
+        auth_config: &AuthConfig,
         code: &Code,
         callback_code: &str,
     ) -> Result<BearerTokenResponse, TeslatteError> {
-        let url = TOKEN_URL;
         let payload = BearerTokenRequest {
             grant_type: "authorization_code".into(),
             client_id: "ownerapi".into(),
             code: callback_code.into(),
             code_verifier: code.verifier.clone(),
-            redirect_uri: "https://auth.tesla.com/void/callback".into(),
+            redirect_uri: auth_config.redirect_uri.clone(),
         };
-        Self::auth_post(url, &payload).await
+        Self::auth_post(&auth_config.token_url, &payload).await
     }
 
     /// Refresh the internally stored access token using the known refresh token.
+    ///
+    /// The scopes and API URL are taken from the new access token, as in
+    /// [OwnerApi::new_with_auth_config()].
     pub async fn refresh(&mut self) -> Result<(), TeslatteError> {
         match &self.refresh_token {
             None => Err(TeslatteError::NoRefreshToken),
             Some(refresh_token) => {
-                let response = Self::refresh_token(refresh_token).await?;
-                self.access_token = response.access_token;
+                let response = Self::refresh_token(&self.auth_config, refresh_token).await?;
+                self.set_access_token(response.access_token);
                 self.refresh_token = Some(response.refresh_token);
                 Ok(())
             }
@@ -112,19 +329,19 @@ page, where the URL will start with https://auth.tesla.com/void/callback?code=..
     }
 
     pub async fn refresh_token(
+        auth_config: &AuthConfig,
         refresh_token: &RefreshToken,
     ) -> Result<RefreshTokenResponse, TeslatteError> {
-        let url = "https://auth.tesla.com/oauth2/v3/token";
         let payload = RefreshTokenRequest {
             grant_type: "refresh_token".into(),
             client_id: "ownerapi".into(),
             refresh_token: refresh_token.0.clone(),
             scope: "openid email offline_access".into(),
         };
-        Self::auth_post(url, &payload).await
+        Self::auth_post(&auth_config.token_url, &payload).await
     }
 
-    async fn auth_post<'a, S, D>(url: &str, payload: &S) -> Result<D, TeslatteError>
+    async fn auth_post<S, D>(url: &str, payload: &S) -> Result<D, TeslatteError>
     where
         S: Serialize,
         D: DeserializeOwned,
@@ -136,7 +353,7 @@ page, where the URL will start with https://auth.tesla.com/void/callback?code=..
             .send()
             .await
             .map_err(|source| TeslatteError::FetchError {
-                source,
+                source: source.without_url(),
                 request: url.to_string(),
             })?;
 
@@ -144,27 +361,27 @@ page, where the URL will start with https://auth.tesla.com/void/callback?code=..
             .text()
             .await
             .map_err(|source| TeslatteError::FetchError {
-                source,
+                source: source.without_url(),
                 request: url.to_string(),
             })?;
 
         let json =
             serde_json::from_str::<D>(&body).map_err(|source| TeslatteError::DecodeJsonError {
                 source,
-                body: body.to_string(),
+                body: redact::redact(&body),
                 request: url.to_string(),
             })?;
 
         Ok(json)
     }
 
-    pub fn login_url(code: &Code, state: &str) -> String {
-        let mut url = Url::parse(AUTHORIZE_URL).unwrap();
+    pub fn login_url(auth_config: &AuthConfig, code: &Code, state: &str) -> String {
+        let mut url = Url::parse(&auth_config.authorize_url).unwrap();
         let mut query = url.query_pairs_mut();
         query.append_pair("client_id", "ownerapi");
         query.append_pair("code_challenge", &code.challenge);
         query.append_pair("code_challenge_method", "S256");
-        query.append_pair("redirect_uri", "https://auth.tesla.com/void/callback");
+        query.append_pair("redirect_uri", &auth_config.redirect_uri);
         query.append_pair("response_type", "code");
         query.append_pair("scope", "openid email offline_access");
         query.append_pair("state", state);
@@ -216,6 +433,7 @@ pub struct LoginForm {
     pub url: String,
     pub code: Code,
     pub state: String,
+    pub auth_config: AuthConfig,
 }
 
 #[derive(Debug, Serialize)]
@@ -286,3 +504,131 @@ pub fn ask_input(prompt: &str) -> String {
         .expect("Failed to read line of user input.");
     s.trim().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(payload: &str) -> AccessToken {
+        let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256","typ":"JWT"}"#);
+        let payload = URL_SAFE_NO_PAD.encode(payload);
+        AccessToken(format!("{header}.{payload}.signature"))
+    }
+
+    #[test]
+    fn access_token_claims() {
+        let token = jwt(r#"{
+                "iss": "https://auth.tesla.com/oauth2/v3/nts",
+                "azp": "ownerapi",
+                "sub": "01234567-89ab-cdef-0123-456789abcdef",
+                "aud": [
+                    "https://fleet-api.prd.na.vn.cloud.tesla.com",
+                    "https://auth.tesla.com/oauth2/v3/userinfo"
+                ],
+                "scp": ["openid", "offline_access", "vehicle_device_data"],
+                "amr": ["pwd", "mfa", "otp"],
+                "exp": 1700028800,
+                "iat": 1700000000,
+                "ou_code": "NA",
+                "locale": "en-US"
+            }"#);
+
+        let claims = token.claims().unwrap();
+        assert_eq!(claims.sub, "01234567-89ab-cdef-0123-456789abcdef");
+        assert_eq!(
+            claims.expires_at() - claims.issued_at(),
+            TimeDelta::hours(8)
+        );
+        assert!(claims.is_expired());
+        assert!(claims.has_scope("vehicle_device_data"));
+        assert!(!claims.has_scope("vehicle_cmds"));
+        assert_eq!(
+            claims.scopes(),
+            vec![
+                Scope::Openid,
+                Scope::OfflineAccess,
+                Scope::VehicleDeviceData
+            ]
+        );
+        assert_eq!(
+            claims.fleet_api_audience(),
+            Some("https://fleet-api.prd.na.vn.cloud.tesla.com")
+        );
+    }
+
+    #[test]
+    fn access_token_claims_single_audience() {
+        let token = jwt(r#"{
+                "iss": "https://auth.tesla.com/oauth2/v3",
+                "sub": "abc",
+                "aud": "https://owner-api.teslamotors.com/",
+                "exp": 1700028800,
+                "iat": 1700000000
+            }"#);
+
+        let claims = token.claims().unwrap();
+        assert_eq!(claims.aud, vec!["https://owner-api.teslamotors.com/"]);
+        assert!(claims.scp.is_empty());
+        assert_eq!(claims.fleet_api_audience(), None);
+    }
+
+    #[test]
+    fn new_access_token_sets_scopes_and_api_url() {
+        let mut api = OwnerApi::new(AccessToken::new("qts-abcdef"), None);
+        assert_eq!(api.scopes, None);
+        assert_eq!(api.api_url, AuthConfig::default().api_url);
+
+        api.set_access_token(jwt(r#"{
+                "iss": "https://auth.tesla.com/oauth2/v3/nts",
+                "sub": "abc",
+                "aud": ["https://fleet-api.prd.eu.vn.cloud.tesla.com/"],
+                "scp": ["openid", "vehicle_cmds"],
+                "exp": 1700028800,
+                "iat": 1700000000
+            }"#));
+        assert_eq!(api.scopes, Some(vec![Scope::Openid, Scope::VehicleCmds]));
+        assert_eq!(
+            api.api_url,
+            "https://fleet-api.prd.eu.vn.cloud.tesla.com/api/1"
+        );
+
+        api.set_access_token(AccessToken::new("qts-abcdef"));
+        assert_eq!(api.scopes, None);
+        assert_eq!(api.api_url, AuthConfig::default().api_url);
+    }
+
+    #[test]
+    fn china_login_url() {
+        let auth_config = AuthConfig::new(AuthRegion::China);
+        let url = OwnerApi::login_url(&auth_config, &Code::default(), "state");
+        assert!(url.starts_with("https://auth.tesla.cn/oauth2/v3/authorize?"));
+        assert!(url.contains("redirect_uri=https%3A%2F%2Fauth.tesla.cn%2Fvoid%2Fcallback"));
+        assert_eq!(
+            auth_config.api_url,
+            "https://owner-api.vn.cloud.tesla.cn/api/1"
+        );
+    }
+
+    #[test]
+    fn tokens_are_redacted() {
+        let token = AccessToken::new("secret");
+        assert_eq!(format!("{token}"), "[REDACTED]");
+        assert_eq!(format!("{token:?}"), r#"AccessToken("[REDACTED]")"#);
+        assert_eq!(token.expose(), "secret");
+
+        let token = RefreshToken::new("secret");
+        assert_eq!(
+            format!("{token} {token:?}"),
+            r#"[REDACTED] RefreshToken("[REDACTED]")"#
+        );
+    }
+
+    #[test]
+    fn access_token_not_a_jwt() {
+        let token = AccessToken("qts-abcdef".to_string());
+        assert!(matches!(
+            token.claims(),
+            Err(TeslatteError::DecodeAccessTokenError(_))
+        ));
+    }
+}
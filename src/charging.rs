@@ -0,0 +1,411 @@
+use crate::error::TeslatteError;
+use crate::{check_scope, join_query_pairs, pub_get_args, rfc3339, ApiValues, OwnerApi};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[rustfmt::skip]
+impl OwnerApi {
+    pub_get_args!(charging_history, ChargingHistory, "/dx/charging/history", ChargingHistoryValues, [VehicleChargingCmds]);
+    pub_get_args!(charging_sessions, ChargingSessions, "/dx/charging/sessions", ChargingSessionsValues, [VehicleChargingCmds]);
+}
+
+impl OwnerApi {
+    /// Every page of [OwnerApi::charging_history()], starting at [ChargingHistoryValues::page_no].
+    pub async fn charging_history_all_pages(
+        &self,
+        values: &ChargingHistoryValues,
+    ) -> Result<Vec<ChargingHistoryRecord>, TeslatteError> {
+        let mut values = values.clone();
+        let mut page_no = values.page_no.unwrap_or(1);
+        let mut records = vec![];
+        loop {
+            values.page_no = Some(page_no);
+            let history = self.charging_history(&values).await?;
+            let empty = history.data.is_empty();
+            records.extend(history.data);
+            if empty || history.has_more_data != Some(true) {
+                return Ok(records);
+            }
+            page_no += 1;
+        }
+    }
+
+    /// The PDF of an invoice, from [ChargingInvoice::content_id].
+    pub async fn charging_invoice(&self, content_id: &str) -> Result<Vec<u8>, TeslatteError> {
+        check_scope!(self, charging_invoice, [VehicleChargingCmds])?;
+        let url = format!(
+            "{}/dx/charging/invoice/{}",
+            self.api_url,
+            urlencoding::encode(content_id)
+        );
+        self.get_bytes(&url).await
+    }
+}
+
+/// Query for [OwnerApi::charging_history()]. Every field is optional.
+#[derive(Debug, Clone, Default)]
+pub struct ChargingHistoryValues {
+    pub vin: Option<String>,
+    pub start_time: Option<DateTime<FixedOffset>>,
+    pub end_time: Option<DateTime<FixedOffset>>,
+    /// Starts at 1.
+    pub page_no: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl ApiValues for ChargingHistoryValues {
+    fn format(&self, url: &str) -> String {
+        let mut pairs: Vec<(&str, String)> = vec![];
+        if let Some(vin) = &self.vin {
+            pairs.push(("vin", vin.clone()));
+        }
+        if let Some(start_time) = self.start_time {
+            pairs.push(("startTime", rfc3339(&start_time)));
+        }
+        if let Some(end_time) = self.end_time {
+            pairs.push(("endTime", rfc3339(&end_time)));
+        }
+        if let Some(page_no) = self.page_no {
+            pairs.push(("pageNo", page_no.to_string()));
+        }
+        if let Some(page_size) = self.page_size {
+            pairs.push(("pageSize", page_size.to_string()));
+        }
+        query_url(url, &pairs)
+    }
+}
+
+/// Query for [OwnerApi::charging_sessions()], which is only available to business fleets.
+#[derive(Debug, Clone, Default)]
+pub struct ChargingSessionsValues {
+    pub vin: Option<String>,
+    pub date_from: Option<DateTime<FixedOffset>>,
+    pub date_to: Option<DateTime<FixedOffset>>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+impl ApiValues for ChargingSessionsValues {
+    fn format(&self, url: &str) -> String {
+        let mut pairs: Vec<(&str, String)> = vec![];
+        if let Some(vin) = &self.vin {
+            pairs.push(("vin", vin.clone()));
+        }
+        if let Some(date_from) = self.date_from {
+            pairs.push(("date_from", rfc3339(&date_from)));
+        }
+        if let Some(date_to) = self.date_to {
+            pairs.push(("date_to", rfc3339(&date_to)));
+        }
+        if let Some(limit) = self.limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = self.offset {
+            pairs.push(("offset", offset.to_string()));
+        }
+        query_url(url, &pairs)
+    }
+}
+
+fn query_url(url: &str, pairs: &[(&str, String)]) -> String {
+    if pairs.is_empty() {
+        url.to_string()
+    } else {
+        format!("{}?{}", url, join_query_pairs(pairs))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChargingHistory {
+    pub data: Vec<ChargingHistoryRecord>,
+    pub total_results: Option<i64>,
+    pub has_more_data: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChargingHistoryRecord {
+    pub session_id: i64,
+    pub vin: String,
+    pub site_location_name: Option<String>,
+    pub charge_start_date_time: Option<DateTime<FixedOffset>>,
+    pub charge_stop_date_time: Option<DateTime<FixedOffset>>,
+    pub unlatch_date_time: Option<DateTime<FixedOffset>>,
+    pub country_code: Option<String>,
+    #[serde(default)]
+    pub fees: Vec<ChargingFee>,
+    pub billing_type: Option<String>,
+    #[serde(default)]
+    pub invoices: Vec<ChargingInvoice>,
+    pub vehicle_make_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChargingFee {
+    pub session_fee_id: Option<i64>,
+    /// e.g. `CHARGING` or `PARKING`.
+    pub fee_type: String,
+    pub currency_code: Option<String>,
+    pub pricing_type: Option<String>,
+    pub rate_base: Option<f64>,
+    pub rate_tier1: Option<f64>,
+    pub rate_tier2: Option<f64>,
+    pub rate_tier3: Option<f64>,
+    pub rate_tier4: Option<f64>,
+    pub usage_base: Option<f64>,
+    pub usage_tier1: Option<f64>,
+    pub usage_tier2: Option<f64>,
+    pub usage_tier3: Option<f64>,
+    pub usage_tier4: Option<f64>,
+    pub total_base: Option<f64>,
+    pub total_tier1: Option<f64>,
+    pub total_tier2: Option<f64>,
+    pub total_tier3: Option<f64>,
+    pub total_tier4: Option<f64>,
+    pub total_due: Option<f64>,
+    pub net_due: Option<f64>,
+    /// Unit of the usage, e.g. `kwh` or `min`.
+    pub uom: Option<String>,
+    pub is_paid: Option<bool>,
+    pub status: Option<String>,
+}
+
+impl ChargingFee {
+    /// Usage over the base rate and all of the tiers, in [ChargingFee::uom].
+    pub fn usage(&self) -> f64 {
+        [
+            self.usage_base,
+            self.usage_tier1,
+            self.usage_tier2,
+            self.usage_tier3,
+            self.usage_tier4,
+        ]
+        .iter()
+        .flatten()
+        .sum()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChargingInvoice {
+    pub file_name: String,
+    /// Pass to [OwnerApi::charging_invoice()] to download the PDF.
+    pub content_id: String,
+    pub invoice_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChargingSessions {
+    pub data: Vec<BusinessChargingSession>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BusinessChargingSession {
+    pub id: String,
+    pub vin: String,
+    pub location: Option<BusinessChargingLocation>,
+    pub start_date_time: Option<DateTime<FixedOffset>>,
+    pub stop_date_time: Option<DateTime<FixedOffset>>,
+    /// kWh.
+    pub total_energy: Option<f64>,
+    pub total_cost: Option<BusinessChargingCost>,
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BusinessChargingLocation {
+    pub name: Option<String>,
+    pub country: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BusinessChargingCost {
+    pub excl_vat: Option<f64>,
+    pub incl_vat: Option<f64>,
+    pub total_due: Option<f64>,
+}
+
+/// A charging session from either [ChargingHistoryRecord] or [BusinessChargingSession], with
+/// the totals worked out, e.g. for exporting.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChargingSession {
+    pub id: String,
+    pub vin: String,
+    pub site: Option<String>,
+    pub start: Option<DateTime<FixedOffset>>,
+    pub end: Option<DateTime<FixedOffset>>,
+    pub energy_kwh: f64,
+    /// Total of all of the fees, including parking.
+    pub fees: f64,
+    pub currency: Option<String>,
+}
+
+impl From<&ChargingHistoryRecord> for ChargingSession {
+    fn from(record: &ChargingHistoryRecord) -> Self {
+        let energy_kwh = record
+            .fees
+            .iter()
+            .filter(|fee| fee.fee_type == "CHARGING")
+            .filter(|fee| {
+                fee.uom
+                    .as_deref()
+                    .is_some_and(|uom| uom.eq_ignore_ascii_case("kwh"))
+            })
+            .map(ChargingFee::usage)
+            .sum();
+        let fees = record.fees.iter().filter_map(|fee| fee.total_due).sum();
+        let currency = record.fees.iter().find_map(|fee| fee.currency_code.clone());
+
+        Self {
+            id: record.session_id.to_string(),
+            vin: record.vin.clone(),
+            site: record.site_location_name.clone(),
+            start: record.charge_start_date_time,
+            end: record.charge_stop_date_time,
+            energy_kwh,
+            fees,
+            currency,
+        }
+    }
+}
+
+impl From<&BusinessChargingSession> for ChargingSession {
+    fn from(session: &BusinessChargingSession) -> Self {
+        let cost = session.total_cost.as_ref();
+        Self {
+            id: session.id.clone(),
+            vin: session.vin.clone(),
+            site: session.location.as_ref().and_then(|l| l.name.clone()),
+            start: session.start_date_time,
+            end: session.stop_date_time,
+            energy_kwh: session.total_energy.unwrap_or_default(),
+            fees: cost
+                .and_then(|c| c.total_due.or(c.incl_vat))
+                .unwrap_or_default(),
+            currency: session.currency.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PrintResponses, RequestData};
+
+    #[test]
+    fn charging_history_query() {
+        let values = ChargingHistoryValues::default();
+        assert_eq!(
+            values.format("/dx/charging/history"),
+            "/dx/charging/history"
+        );
+
+        let values = ChargingHistoryValues {
+            vin: Some("5YJ3E1EA1KF000000".to_string()),
+            start_time: Some(DateTime::parse_from_rfc3339("2023-12-01T00:00:00+10:00").unwrap()),
+            end_time: None,
+            page_no: Some(2),
+            page_size: Some(50),
+        };
+        assert_eq!(
+            values.format("/dx/charging/history"),
+            "/dx/charging/history?vin=5YJ3E1EA1KF000000&startTime=2023-12-01T00:00:00%2B10:00&pageNo=2&pageSize=50"
+        );
+    }
+
+    #[test]
+    fn json_charging_history() {
+        let s = r#"{
+          "response": {
+            "data": [
+              {
+                "sessionId": 1234567,
+                "vin": "5YJ3E1EA1KF000000",
+                "siteLocationName": "Lindon, UT",
+                "chargeStartDateTime": "2023-12-06T20:46:54-07:00",
+                "chargeStopDateTime": "2023-12-06T21:28:04-07:00",
+                "unlatchDateTime": "2023-12-06T21:28:12-07:00",
+                "countryCode": "US",
+                "fees": [
+                  {
+                    "sessionFeeId": 1,
+                    "feeType": "CHARGING",
+                    "currencyCode": "USD",
+                    "pricingType": "PAYMENT",
+                    "rateBase": 0.46,
+                    "rateTier1": 0,
+                    "rateTier2": 0,
+                    "rateTier3": null,
+                    "rateTier4": null,
+                    "usageBase": 40,
+                    "usageTier1": 0,
+                    "usageTier2": 24,
+                    "usageTier3": null,
+                    "usageTier4": null,
+                    "totalBase": 18.4,
+                    "totalTier1": 0,
+                    "totalTier2": 0,
+                    "totalTier3": 0,
+                    "totalTier4": 0,
+                    "totalDue": 18.4,
+                    "netDue": 18.4,
+                    "uom": "kwh",
+                    "isPaid": true,
+                    "status": "PAID"
+                  },
+                  {
+                    "sessionFeeId": 2,
+                    "feeType": "PARKING",
+                    "currencyCode": "USD",
+                    "pricingType": "NO_CHARGE",
+                    "usageBase": 0,
+                    "totalDue": 1.0,
+                    "netDue": 0,
+                    "uom": "min",
+                    "isPaid": true,
+                    "status": "PAID"
+                  }
+                ],
+                "billingType": "IMMEDIATE",
+                "invoices": [
+                  {
+                    "fileName": "INV-2023-1234567.pdf",
+                    "contentId": "abc-123",
+                    "invoiceType": "IMMEDIATE"
+                  }
+                ],
+                "vehicleMakeType": "TSLA"
+              }
+            ],
+            "totalResults": 1,
+            "hasMoreData": false
+          }
+        }"#;
+
+        let request_data = RequestData::Get { url: "" };
+        let history = OwnerApi::parse_json::<ChargingHistory>(
+            &request_data,
+            s.to_string(),
+            PrintResponses::No,
+        )
+        .unwrap();
+        assert_eq!(history.has_more_data, Some(false));
+
+        let record = &history.data[0];
+        assert_eq!(record.invoices[0].content_id, "abc-123");
+
+        let session = ChargingSession::from(record);
+        assert_eq!(session.id, "1234567");
+        assert_eq!(session.site.as_deref(), Some("Lindon, UT"));
+        assert_eq!(session.energy_kwh, 64.0);
+        assert!((session.fees - 19.4).abs() < 0.001);
+        assert_eq!(session.currency.as_deref(), Some("USD"));
+        assert_eq!(
+            session.end.unwrap() - session.start.unwrap(),
+            chrono::TimeDelta::seconds(41 * 60 + 10)
+        );
+    }
+}
@@ -1,3 +1,30 @@
+pub mod charging;
 pub mod energy;
+pub mod fleet_telemetry;
 pub mod powerwall;
+pub mod sharing;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+pub mod token;
 pub mod vehicle;
+pub mod vin;
+
+/// Print rows in columns, padded to the widest cell.
+pub fn print_table<const N: usize>(header: [&str; N], rows: Vec<[String; N]>) {
+    let mut widths = header.map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for row in std::iter::once(header.map(String::from)).chain(rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
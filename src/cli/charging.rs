@@ -0,0 +1,198 @@
+use crate::charging::{ChargingHistoryValues, ChargingSession, ChargingSessionsValues};
+use crate::OwnerApi;
+use chrono::{DateTime, FixedOffset};
+use clap::{Args, Subcommand};
+use miette::{miette, IntoDiagnostic, WrapErr};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Subcommand)]
+pub enum ChargingCommand {
+    /// Supercharger charging history of the account.
+    History(ChargingHistoryArgs),
+
+    /// Charging sessions of a business fleet.
+    Sessions(ChargingSessionsArgs),
+
+    /// Download the PDF of an invoice.
+    Invoice {
+        /// `contentId` of the invoice in the charging history.
+        content_id: String,
+
+        /// Defaults to `<content_id>.pdf`.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Example:
+///
+/// teslatte api charging history --all -s 2024-01-01T00:00:00Z --csv charging.csv --invoices invoices
+#[derive(Debug, Args)]
+pub struct ChargingHistoryArgs {
+    #[clap(long)]
+    pub vin: Option<String>,
+
+    /// ISO8601 date-time for the start of the period, e.g. 2024-01-01T00:00:00Z
+    #[clap(short, long)]
+    pub start: Option<DateTime<FixedOffset>>,
+
+    /// ISO8601 date-time for the end of the period, e.g. 2025-01-01T00:00:00Z
+    #[clap(short, long)]
+    pub end: Option<DateTime<FixedOffset>>,
+
+    /// Page to get, starting at 1.
+    #[clap(long)]
+    pub page_no: Option<u32>,
+
+    #[clap(long)]
+    pub page_size: Option<u32>,
+
+    /// Get every page from `page_no` onwards.
+    #[clap(short, long)]
+    pub all: bool,
+
+    /// Write the sessions to a CSV file.
+    #[clap(long)]
+    pub csv: Option<PathBuf>,
+
+    /// Save the invoice PDFs of the sessions to a directory.
+    #[clap(long)]
+    pub invoices: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ChargingSessionsArgs {
+    #[clap(long)]
+    pub vin: Option<String>,
+
+    /// ISO8601 date-time for the start of the period, e.g. 2024-01-01T00:00:00Z
+    #[clap(short, long)]
+    pub start: Option<DateTime<FixedOffset>>,
+
+    /// ISO8601 date-time for the end of the period, e.g. 2025-01-01T00:00:00Z
+    #[clap(short, long)]
+    pub end: Option<DateTime<FixedOffset>>,
+
+    #[clap(long)]
+    pub limit: Option<u32>,
+
+    #[clap(long)]
+    pub offset: Option<u32>,
+
+    /// Write the sessions to a CSV file.
+    #[clap(long)]
+    pub csv: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ChargingArgs {
+    #[clap(subcommand)]
+    pub command: ChargingCommand,
+}
+
+impl ChargingArgs {
+    pub async fn run(&self, api: &OwnerApi) -> miette::Result<()> {
+        match &self.command {
+            ChargingCommand::History(args) => {
+                let values = ChargingHistoryValues {
+                    vin: args.vin.clone(),
+                    start_time: args.start,
+                    end_time: args.end,
+                    page_no: args.page_no,
+                    page_size: args.page_size,
+                };
+                let records = if args.all {
+                    api.charging_history_all_pages(&values).await?
+                } else {
+                    api.charging_history(&values).await?.data
+                };
+
+                if let Some(path) = &args.csv {
+                    let sessions = records
+                        .iter()
+                        .map(ChargingSession::from)
+                        .collect::<Vec<_>>();
+                    write_csv(path, &sessions)?;
+                }
+
+                if let Some(dir) = &args.invoices {
+                    std::fs::create_dir_all(dir)
+                        .into_diagnostic()
+                        .wrap_err_with(|| format!("Creating {}", dir.display()))?;
+                    for invoice in records.iter().flat_map(|r| &r.invoices) {
+                        // Only keep the file name, so the API can't pick where it is written.
+                        let file_name =
+                            Path::new(&invoice.file_name).file_name().ok_or_else(|| {
+                                miette!("Bad invoice file name: {}", invoice.file_name)
+                            })?;
+                        let pdf = api.charging_invoice(&invoice.content_id).await?;
+                        write_file(&dir.join(file_name), &pdf)?;
+                    }
+                }
+            }
+            ChargingCommand::Sessions(args) => {
+                let values = ChargingSessionsValues {
+                    vin: args.vin.clone(),
+                    date_from: args.start,
+                    date_to: args.end,
+                    limit: args.limit,
+                    offset: args.offset,
+                };
+                let sessions = api.charging_sessions(&values).await?;
+
+                if let Some(path) = &args.csv {
+                    let sessions = sessions
+                        .data
+                        .iter()
+                        .map(ChargingSession::from)
+                        .collect::<Vec<_>>();
+                    write_csv(path, &sessions)?;
+                }
+            }
+            ChargingCommand::Invoice { content_id, output } => {
+                let pdf = api.charging_invoice(content_id).await?;
+                let path = output
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(format!("{content_id}.pdf")));
+                write_file(&path, &pdf)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_csv(path: &Path, sessions: &[ChargingSession]) -> miette::Result<()> {
+    let mut csv = String::from("id,vin,site,start,end,energy_kwh,fees,currency\n");
+    for session in sessions {
+        let row = [
+            session.id.clone(),
+            session.vin.clone(),
+            session.site.clone().unwrap_or_default(),
+            session.start.map(|d| d.to_rfc3339()).unwrap_or_default(),
+            session.end.map(|d| d.to_rfc3339()).unwrap_or_default(),
+            session.energy_kwh.to_string(),
+            session.fees.to_string(),
+            session.currency.clone().unwrap_or_default(),
+        ];
+        let row = row.iter().map(|f| csv_field(f)).collect::<Vec<_>>();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    write_file(path, csv.as_bytes())
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> miette::Result<()> {
+    std::fs::write(path, contents)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("Writing {}", path.display()))?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
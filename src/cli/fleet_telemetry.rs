@@ -0,0 +1,113 @@
+use crate::fleet_telemetry::{
+    FleetTelemetryConfig, FleetTelemetryConfigRequest, TelemetryAlertType, TelemetryField,
+};
+use crate::OwnerApi;
+use chrono::{TimeDelta, Utc};
+use clap::{Args, Subcommand};
+use miette::{IntoDiagnostic, WrapErr};
+use std::path::PathBuf;
+
+#[derive(Debug, Subcommand)]
+pub enum FleetTelemetryCommand {
+    /// Send a config to one or more vehicles.
+    Configure(ConfigureArgs),
+
+    /// Config of a vehicle, and whether it was received.
+    Config { vin: String },
+
+    /// Stop a vehicle streaming.
+    Delete { vin: String },
+
+    /// Errors the vehicle had connecting to the server.
+    Errors { vin: String },
+}
+
+/// Example:
+///
+/// teslatte api fleet-telemetry configure --hostname telemetry.example.com --ca ca.pem -f BatteryLevel=60 -f Location=10 VIN1 VIN2
+#[derive(Debug, Args)]
+pub struct ConfigureArgs {
+    /// VINs of the vehicles.
+    #[clap(required = true)]
+    pub vins: Vec<String>,
+
+    /// Hostname of the Fleet Telemetry server.
+    #[clap(long)]
+    pub hostname: String,
+
+    /// PEM file with the certificate chain of the server's certificate authority.
+    #[clap(long)]
+    pub ca: PathBuf,
+
+    #[clap(long)]
+    pub port: Option<u16>,
+
+    /// Field to stream and how often, in seconds, e.g. BatteryLevel=60. Can be repeated.
+    #[clap(short, long = "field", value_parser = parse_field, required = true)]
+    pub fields: Vec<(TelemetryField, u32)>,
+
+    /// `service` or `customer`. Can be repeated.
+    #[clap(short, long = "alert-type")]
+    pub alert_types: Vec<TelemetryAlertType>,
+
+    /// Days until the vehicles stop streaming, up to a year.
+    #[clap(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..=365))]
+    pub expires_in_days: u32,
+}
+
+fn parse_field(s: &str) -> Result<(TelemetryField, u32), String> {
+    let (field, interval) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected FIELD=SECONDS, got {s}"))?;
+    let field = field
+        .parse()
+        .map_err(|_| format!("Unknown field: {field}"))?;
+    let interval = interval
+        .parse()
+        .map_err(|_| format!("Bad interval: {interval}"))?;
+    Ok((field, interval))
+}
+
+#[derive(Debug, Args)]
+pub struct FleetTelemetryArgs {
+    #[clap(subcommand)]
+    pub command: FleetTelemetryCommand,
+}
+
+impl FleetTelemetryArgs {
+    pub async fn run(&self, api: &OwnerApi) -> miette::Result<()> {
+        match &self.command {
+            FleetTelemetryCommand::Configure(args) => {
+                let ca = std::fs::read_to_string(&args.ca)
+                    .into_diagnostic()
+                    .wrap_err_with(|| format!("Reading {}", args.ca.display()))?;
+                let exp = Utc::now() + TimeDelta::days(args.expires_in_days.into());
+                let mut config = FleetTelemetryConfig::new(&args.hostname, ca, exp);
+                if let Some(port) = args.port {
+                    config = config.port(port);
+                }
+                for (field, interval) in &args.fields {
+                    config = config.field(*field, *interval);
+                }
+                for alert_type in &args.alert_types {
+                    config = config.alert_type(*alert_type);
+                }
+                let request = FleetTelemetryConfigRequest {
+                    vins: args.vins.clone(),
+                    config,
+                };
+                api.fleet_telemetry_config_create(&request).await?;
+            }
+            FleetTelemetryCommand::Config { vin } => {
+                api.fleet_telemetry_config(vin).await?;
+            }
+            FleetTelemetryCommand::Delete { vin } => {
+                api.fleet_telemetry_config_delete(vin).await?;
+            }
+            FleetTelemetryCommand::Errors { vin } => {
+                api.fleet_telemetry_errors(vin).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use crate::OwnerApi;
+use crate::VehicleId;
+use clap::{Args, Subcommand};
+
+#[derive(Debug, Subcommand)]
+pub enum SharingCommand {
+    /// List the invites to share the vehicle.
+    Invites,
+
+    /// Create an invite to share the vehicle.
+    CreateInvite,
+
+    /// Revoke an invite. Drivers that redeemed it lose access.
+    RevokeInvite { invite_id: i64 },
+
+    /// List the drivers the vehicle is shared with.
+    Drivers,
+
+    /// Remove a driver, using their `user_id`.
+    RemoveDriver { share_user_id: i64 },
+}
+
+#[derive(Debug, Args)]
+pub struct SharingArgs {
+    #[clap(subcommand)]
+    pub command: SharingCommand,
+}
+
+impl SharingArgs {
+    pub async fn run(&self, api: &OwnerApi, vehicle_id: &VehicleId) -> miette::Result<()> {
+        match self.command {
+            SharingCommand::Invites => {
+                api.share_invites(vehicle_id).await?;
+            }
+            SharingCommand::CreateInvite => {
+                api.share_invites_create(vehicle_id).await?;
+            }
+            SharingCommand::RevokeInvite { invite_id } => {
+                api.share_invites_revoke(vehicle_id, invite_id).await?;
+            }
+            SharingCommand::Drivers => {
+                api.drivers(vehicle_id).await?;
+            }
+            SharingCommand::RemoveDriver { share_user_id } => {
+                api.drivers_remove(vehicle_id, share_user_id).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use crate::telemetry::{TelemetryRecord, TelemetryServer, TelemetryServerConfig, TelemetryTls};
+use clap::{Args, Subcommand};
+use futures_util::StreamExt;
+use miette::miette;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+#[derive(Debug, Subcommand)]
+pub enum TelemetryCommand {
+    /// Receive Fleet Telemetry from vehicles and print it.
+    Serve(ServeArgs),
+}
+
+/// Example:
+///
+/// teslatte telemetry serve --cert server.pem --key server.key --client-ca vehicle_ca.pem
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    #[clap(long, default_value = "0.0.0.0:443")]
+    pub addr: SocketAddr,
+
+    /// PEM file with the server's certificate chain.
+    #[clap(long, required_unless_present = "no_tls")]
+    pub cert: Option<PathBuf>,
+
+    /// PEM file with the server's private key.
+    #[clap(long, required_unless_present = "no_tls")]
+    pub key: Option<PathBuf>,
+
+    /// PEM file with the CA of the vehicles' client certificates.
+    #[clap(long, required_unless_present = "no_tls")]
+    pub client_ca: Option<PathBuf>,
+
+    /// Accept plain websockets, e.g. behind a proxy that terminates TLS.
+    #[clap(long, conflicts_with_all = ["cert", "key", "client_ca"])]
+    pub no_tls: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct TelemetryArgs {
+    #[clap(subcommand)]
+    pub command: TelemetryCommand,
+}
+
+impl TelemetryArgs {
+    pub async fn run(&self) -> miette::Result<()> {
+        match &self.command {
+            TelemetryCommand::Serve(args) => {
+                let mut config = TelemetryServerConfig::new(args.addr);
+                if let (Some(cert), Some(key), Some(client_ca)) =
+                    (&args.cert, &args.key, &args.client_ca)
+                {
+                    config = config.tls(TelemetryTls::from_pem_files(cert, key, client_ca)?);
+                }
+                let mut server = TelemetryServer::bind(config).await?;
+                println!("Listening on {}", server.local_addr());
+                while let Some(record) = server.next().await {
+                    print_record(&record);
+                }
+                Err(miette!("The telemetry server stopped."))
+            }
+        }
+    }
+}
+
+fn print_record(record: &TelemetryRecord) {
+    match record {
+        TelemetryRecord::Data(data) => {
+            for (key, value) in &data.values {
+                println!("{} {key}={value}", data.vin);
+            }
+        }
+        TelemetryRecord::Alerts(alerts) => {
+            for alert in &alerts.alerts {
+                println!(
+                    "{} alert {} ({})",
+                    alerts.vin,
+                    alert.name,
+                    alert.audiences.join(", ")
+                );
+            }
+        }
+        TelemetryRecord::Errors(errors) => {
+            for error in &errors.errors {
+                println!("{} error {}: {}", errors.vin, error.name, error.body);
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::auth::AccessToken;
+use chrono::TimeDelta;
+use clap::{Args, Subcommand};
+
+#[derive(Debug, Subcommand)]
+pub enum TokenCommand {
+    /// Decode the access token and show its expiry, scopes and audience.
+    ///
+    /// This is done offline. The signature of the token is not verified.
+    Info,
+}
+
+#[derive(Debug, Args)]
+pub struct TokenArgs {
+    /// Access token. If not provided, will try to load from the cli.json file.
+    #[clap(short, long, env = "TESLA_ACCESS_TOKEN")]
+    pub access_token: Option<AccessToken>,
+
+    #[clap(subcommand)]
+    pub command: TokenCommand,
+}
+
+impl TokenArgs {
+    pub fn run(&self, access_token: &AccessToken) -> miette::Result<()> {
+        match self.command {
+            TokenCommand::Info => {
+                let claims = access_token.claims()?;
+
+                let remaining = claims.remaining();
+                let remaining = if remaining > TimeDelta::zero() {
+                    format!("in {}", format_duration(remaining))
+                } else {
+                    format!("expired {} ago", format_duration(-remaining))
+                };
+
+                println!("Subject:   {}", claims.sub);
+                println!("Issuer:    {}", claims.iss);
+                println!("Audience:  {}", claims.aud.join(", "));
+                println!("Scopes:    {}", claims.scp.join(" "));
+                println!("Issued at: {}", claims.issued_at());
+                println!("Expires:   {} ({remaining})", claims.expires_at());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_duration(duration: TimeDelta) -> String {
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() % 60;
+    let seconds = duration.num_seconds() % 60;
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
@@ -1,14 +1,69 @@
+use crate::cli::print_table;
+use crate::cli::sharing::SharingArgs;
 use crate::vehicles::{
-    Endpoints, GetVehicleData, SetChargeLimit, SetChargingAmps, SetScheduledCharging,
-    SetScheduledDeparture, SetTemperatures,
+    ActuateTrunk, AddChargeSchedule, AddPreconditionSchedule, AdjustVolume,
+    CabinOverheatProtection, Endpoint, Endpoints, GetVehicleData, NavigationGpsRequest,
+    NavigationRequest, NavigationScRequest, NavigationWaypointsRequest, NearbyChargingSite,
+    NearbyChargingSites, NearbyChargingSitesValues, RemoteAutoSeatClimate,
+    RemoteAutoSteeringWheelHeat, RemoteBoombox, RemoteSeatCooler, RemoteSeatHeater,
+    RemoteSteeringWheelHeatLevel, RemoteSteeringWheelHeater, RemoveSchedule,
+    ScheduleSoftwareUpdate, SetBioweaponMode, SetChargeLimit, SetChargingAmps,
+    SetClimateKeeperMode, SetCopTemp, SetPinToDrive, SetPreconditioningMax, SetScheduledCharging,
+    SetScheduledDeparture, SetSentryMode, SetTemperatures, SetValetMode, SetVehicleName,
+    SpeedLimitPin, SpeedLimitSetLimit, SpeedUnit, SunRoofControl, TriggerHomelink, VehicleState,
+    WindowControl,
 };
-use crate::{OwnerApi, VehicleApi, VehicleId};
+use crate::{OwnerApi, PrintResponses, VehicleApi, VehicleId};
 use clap::{Args, Subcommand};
+use miette::miette;
+use std::str::FromStr;
+use std::time::Duration;
 
 #[derive(Debug, Subcommand)]
 pub enum VehicleCommand {
+    /// Get the vehicle summary, including whether it is online. Doesn't wake up the vehicle.
+    Summary,
+
+    /// List nearby Superchargers and destination chargers, closest first.
+    NearbyChargingSites {
+        /// Number of sites of each type.
+        #[clap(short, long)]
+        count: Option<u32>,
+
+        /// Miles from the vehicle.
+        #[clap(short, long)]
+        radius: Option<u32>,
+
+        /// Only show Superchargers with a free stall.
+        #[clap(short, long)]
+        available: bool,
+    },
+
+    /// Whether mobile access is enabled.
+    MobileEnabled,
+
+    /// Recent alerts of the vehicle.
+    RecentAlerts,
+
+    /// Poll the vehicle until interrupted, while letting it fall asleep when idle.
+    Monitor,
+
+    /// Like `monitor`, but print events such as drives and charging sessions starting.
+    Events,
+
+    /// Print live drive data until interrupted. Doesn't wake up the vehicle.
+    #[cfg(feature = "streaming")]
+    Stream,
+
     /// Get vehicle data.
-    VehicleData(Endpoints),
+    VehicleData {
+        #[clap(flatten)]
+        endpoints: Endpoints,
+
+        /// Don't fetch the data if the vehicle is asleep or offline.
+        #[clap(short = 'o', long)]
+        require_online: bool,
+    },
 
     /// Open the charge port door or unlocks the cable.
     ChargePortDoorOpen,
@@ -40,6 +95,18 @@ pub enum VehicleCommand {
     /// Set scheduled departure.
     SetScheduledDeparture(SetScheduledDeparture),
 
+    /// Add a charge schedule, or update the one with the same ID.
+    AddChargeSchedule(AddChargeSchedule),
+
+    /// Remove a charge schedule.
+    RemoveChargeSchedule(RemoveSchedule),
+
+    /// Add a precondition schedule, or update the one with the same ID.
+    AddPreconditionSchedule(AddPreconditionSchedule),
+
+    /// Remove a precondition schedule.
+    RemovePreconditionSchedule(RemoveSchedule),
+
     /// Wake up
     WakeUp,
 
@@ -58,6 +125,42 @@ pub enum VehicleCommand {
     /// Set the temperature for the HVAC
     HvacTemperature(SetTemperatures),
 
+    /// Set the level of a seat heater.
+    SeatHeater(RemoteSeatHeater),
+
+    /// Set the level of a seat cooler.
+    SeatCooler(RemoteSeatCooler),
+
+    /// Turn automatic seat climate on or off.
+    AutoSeatClimate(RemoteAutoSeatClimate),
+
+    /// Turn the steering wheel heater on or off.
+    SteeringWheelHeater(RemoteSteeringWheelHeater),
+
+    /// Set the level of the steering wheel heater.
+    SteeringWheelHeatLevel(RemoteSteeringWheelHeatLevel),
+
+    /// Turn automatic steering wheel heating on or off.
+    AutoSteeringWheelHeat(RemoteAutoSteeringWheelHeat),
+
+    /// Keep the climate on after leaving the vehicle: off, keep, dog or camp.
+    SetClimateKeeperMode(SetClimateKeeperMode),
+
+    /// Set cabin overheat protection: off, on or fan only.
+    SetCabinOverheatProtection {
+        /// `off`, `on` or `fanonly`.
+        mode: CabinOverheatProtection,
+    },
+
+    /// Set the temperature at which cabin overheat protection starts.
+    SetCopTemp(SetCopTemp),
+
+    /// Turn bioweapon defense mode on or off.
+    SetBioweaponMode(SetBioweaponMode),
+
+    /// Turn max defrost on or off.
+    SetPreconditioningMax(SetPreconditioningMax),
+
     /// Door unlock
     DoorUnlock,
 
@@ -66,6 +169,160 @@ pub enum VehicleCommand {
 
     /// For keyless driving
     RemoteStartDrive,
+
+    /// Open the front trunk, or open/close the rear trunk.
+    ActuateTrunk(ActuateTrunk),
+
+    /// Vent or close the windows.
+    WindowControl(WindowControl),
+
+    /// Vent, close or stop the sunroof.
+    SunRoofControl(SunRoofControl),
+
+    /// Turn sentry mode on or off.
+    SetSentryMode(SetSentryMode),
+
+    /// Turn valet mode on or off.
+    SetValetMode(SetValetMode),
+
+    /// Clear the valet mode PIN.
+    ResetValetPin,
+
+    /// Turn PIN to drive on or off.
+    SetPinToDrive(SetPinToDrive),
+
+    /// Clear the PIN to drive PIN.
+    ResetPinToDrivePin,
+
+    /// Turn on speed limit mode.
+    SpeedLimitActivate(SpeedLimitPin),
+
+    /// Turn off speed limit mode.
+    SpeedLimitDeactivate(SpeedLimitPin),
+
+    /// Set the speed limit. It is checked against the range the vehicle allows first.
+    SpeedLimitSetLimit(SpeedLimitArgs),
+
+    /// Clear the speed limit mode PIN.
+    SpeedLimitClearPin(SpeedLimitPin),
+
+    /// Set the volume. It is checked against the maximum the vehicle allows first.
+    AdjustVolume(AdjustVolumeArgs),
+
+    /// Play or pause the media.
+    MediaTogglePlayback,
+
+    /// Next track.
+    MediaNextTrack,
+
+    /// Previous track.
+    MediaPrevTrack,
+
+    /// Next favorite.
+    MediaNextFav,
+
+    /// Previous favorite.
+    MediaPrevFav,
+
+    /// Turn the volume up one step.
+    MediaVolumeUp,
+
+    /// Turn the volume down one step.
+    MediaVolumeDown,
+
+    /// Send a destination to the vehicle, either an address or `lat,lon`.
+    Navigate(NavigateArgs),
+
+    /// Navigate to a Supercharger.
+    NavigateSupercharger(NavigationScRequest),
+
+    /// Navigate through Google Maps place IDs, in order.
+    NavigateWaypoints {
+        #[clap(required = true)]
+        place_ids: Vec<String>,
+    },
+
+    /// Schedule the available software update.
+    ScheduleSoftwareUpdate(ScheduleSoftwareUpdate),
+
+    /// Cancel the scheduled software update.
+    CancelSoftwareUpdate,
+
+    /// Print the progress of the software update until it is installed. Doesn't wake up the
+    /// vehicle.
+    WaitSoftwareUpdate {
+        /// Seconds between polls.
+        #[clap(short, long, default_value = "60")]
+        interval: u64,
+
+        /// Minutes to wait before giving up.
+        #[clap(short, long, default_value = "240")]
+        timeout: u64,
+    },
+
+    /// Trigger HomeLink. Only works near a HomeLink device.
+    TriggerHomelink(TriggerHomelink),
+
+    /// Play a sound through the external speaker.
+    RemoteBoombox(RemoteBoombox),
+
+    /// Rename the vehicle.
+    SetVehicleName(SetVehicleName),
+
+    /// Share the vehicle with other drivers.
+    Sharing(SharingArgs),
+
+    /// Erase the user data on the touchscreen.
+    EraseUserData {
+        /// Needed, since the data can't be recovered.
+        #[clap(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct SpeedLimitArgs {
+    pub limit: f64,
+
+    /// `mph` or `kph`.
+    #[clap(short, long, default_value = "mph")]
+    pub unit: SpeedUnit,
+}
+
+#[derive(Debug, Args)]
+pub struct AdjustVolumeArgs {
+    pub volume: f64,
+}
+
+#[derive(Debug, Args)]
+pub struct NavigateArgs {
+    /// An address, or anything the vehicle can search for. Coordinates are given as `lat,lon`.
+    #[clap(allow_hyphen_values = true)]
+    pub destination: Destination,
+
+    /// Position of the destination in the route. Only used for coordinates.
+    #[clap(short, long, default_value = "0")]
+    pub order: u32,
+}
+
+#[derive(Debug, Clone)]
+pub enum Destination {
+    Address(String),
+    Coordinates { lat: f64, lon: f64 },
+}
+
+impl FromStr for Destination {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let coordinates = s
+            .split_once(',')
+            .and_then(|(lat, lon)| Some((lat.trim().parse().ok()?, lon.trim().parse().ok()?)));
+        Ok(match coordinates {
+            Some((lat, lon)) => Destination::Coordinates { lat, lon },
+            None => Destination::Address(s.to_string()),
+        })
+    }
 }
 
 #[derive(Debug, Args)]
@@ -79,8 +336,47 @@ pub struct VehicleArgs {
 impl VehicleArgs {
     pub async fn run(self, api: &OwnerApi) -> miette::Result<()> {
         match self.command {
-            VehicleCommand::VehicleData(endpoints) => {
-                let get_vehicle_data = GetVehicleData::new_with_endpoints(self.id, endpoints);
+            VehicleCommand::Summary => {
+                api.vehicle(&self.id).await?;
+            }
+            VehicleCommand::NearbyChargingSites {
+                count,
+                radius,
+                available,
+            } => {
+                let mut values = NearbyChargingSitesValues::new(self.id.clone());
+                values.count = count;
+                values.radius = radius;
+                // The sites are printed as a table below instead.
+                let api = OwnerApi {
+                    print_responses: PrintResponses::No,
+                    ..api.clone()
+                };
+                let sites = api.nearby_charging_sites(&values).await?;
+                print_nearby_charging_sites(&sites, available);
+            }
+            VehicleCommand::MobileEnabled => {
+                api.mobile_enabled(&self.id).await?;
+            }
+            VehicleCommand::RecentAlerts => {
+                api.recent_alerts(&self.id).await?;
+            }
+            VehicleCommand::Monitor => {
+                print_monitor(api, self.id).await?;
+            }
+            VehicleCommand::Events => {
+                print_events(api, self.id).await?;
+            }
+            #[cfg(feature = "streaming")]
+            VehicleCommand::Stream => {
+                print_stream(api, &self.id).await?;
+            }
+            VehicleCommand::VehicleData {
+                endpoints,
+                require_online,
+            } => {
+                let mut get_vehicle_data = GetVehicleData::new_with_endpoints(self.id, endpoints);
+                get_vehicle_data.require_online = require_online;
                 api.vehicle_data(&get_vehicle_data).await?;
             }
             VehicleCommand::SetChargeLimit(limit) => {
@@ -113,6 +409,19 @@ impl VehicleArgs {
             VehicleCommand::SetScheduledDeparture(departure) => {
                 api.set_scheduled_departure(&self.id, &departure).await?;
             }
+            VehicleCommand::AddChargeSchedule(schedule) => {
+                api.add_charge_schedule(&self.id, &schedule).await?;
+            }
+            VehicleCommand::RemoveChargeSchedule(schedule) => {
+                api.remove_charge_schedule(&self.id, &schedule).await?;
+            }
+            VehicleCommand::AddPreconditionSchedule(schedule) => {
+                api.add_precondition_schedule(&self.id, &schedule).await?;
+            }
+            VehicleCommand::RemovePreconditionSchedule(schedule) => {
+                api.remove_precondition_schedule(&self.id, &schedule)
+                    .await?;
+            }
             VehicleCommand::WakeUp => {
                 api.wake_up(&self.id).await?;
             }
@@ -131,6 +440,44 @@ impl VehicleArgs {
             VehicleCommand::HvacTemperature(temps) => {
                 api.set_temps(&self.id, &temps).await?;
             }
+            VehicleCommand::SeatHeater(heater) => {
+                api.remote_seat_heater_request(&self.id, &heater).await?;
+            }
+            VehicleCommand::SeatCooler(cooler) => {
+                api.remote_seat_cooler_request(&self.id, &cooler).await?;
+            }
+            VehicleCommand::AutoSeatClimate(auto_seat_climate) => {
+                api.remote_auto_seat_climate_request(&self.id, &auto_seat_climate)
+                    .await?;
+            }
+            VehicleCommand::SteeringWheelHeater(heater) => {
+                api.remote_steering_wheel_heater_request(&self.id, &heater)
+                    .await?;
+            }
+            VehicleCommand::SteeringWheelHeatLevel(level) => {
+                api.remote_steering_wheel_heat_level_request(&self.id, &level)
+                    .await?;
+            }
+            VehicleCommand::AutoSteeringWheelHeat(auto_heat) => {
+                api.remote_auto_steering_wheel_heat_climate_request(&self.id, &auto_heat)
+                    .await?;
+            }
+            VehicleCommand::SetClimateKeeperMode(mode) => {
+                api.set_climate_keeper_mode(&self.id, &mode).await?;
+            }
+            VehicleCommand::SetCabinOverheatProtection { mode } => {
+                api.set_cabin_overheat_protection(&self.id, &mode.into())
+                    .await?;
+            }
+            VehicleCommand::SetCopTemp(temp) => {
+                api.set_cop_temp(&self.id, &temp).await?;
+            }
+            VehicleCommand::SetBioweaponMode(bioweapon_mode) => {
+                api.set_bioweapon_mode(&self.id, &bioweapon_mode).await?;
+            }
+            VehicleCommand::SetPreconditioningMax(max) => {
+                api.set_preconditioning_max(&self.id, &max).await?;
+            }
             VehicleCommand::DoorUnlock => {
                 api.door_unlock(&self.id).await?;
             }
@@ -140,7 +487,272 @@ impl VehicleArgs {
             VehicleCommand::RemoteStartDrive => {
                 api.remote_start_drive(&self.id).await?;
             }
+            VehicleCommand::ActuateTrunk(trunk) => {
+                api.actuate_trunk(&self.id, &trunk).await?;
+            }
+            VehicleCommand::WindowControl(window) => {
+                api.window_control(&self.id, &window).await?;
+            }
+            VehicleCommand::SunRoofControl(sun_roof) => {
+                api.sun_roof_control(&self.id, &sun_roof).await?;
+            }
+            VehicleCommand::SetSentryMode(sentry_mode) => {
+                api.set_sentry_mode(&self.id, &sentry_mode).await?;
+            }
+            VehicleCommand::SetValetMode(valet_mode) => {
+                api.set_valet_mode(&self.id, &valet_mode).await?;
+            }
+            VehicleCommand::ResetValetPin => {
+                api.reset_valet_pin(&self.id).await?;
+            }
+            VehicleCommand::SetPinToDrive(pin_to_drive) => {
+                api.set_pin_to_drive(&self.id, &pin_to_drive).await?;
+            }
+            VehicleCommand::ResetPinToDrivePin => {
+                api.reset_pin_to_drive_pin(&self.id).await?;
+            }
+            VehicleCommand::SpeedLimitActivate(pin) => {
+                api.speed_limit_activate(&self.id, &pin).await?;
+            }
+            VehicleCommand::SpeedLimitDeactivate(pin) => {
+                api.speed_limit_deactivate(&self.id, &pin).await?;
+            }
+            VehicleCommand::SpeedLimitSetLimit(args) => {
+                let vehicle_state = vehicle_state(api, &self.id).await?;
+                let limit = SpeedLimitSetLimit::new(
+                    args.limit,
+                    args.unit,
+                    &vehicle_state.speed_limit_mode,
+                )?;
+                api.speed_limit_set_limit(&self.id, &limit).await?;
+            }
+            VehicleCommand::SpeedLimitClearPin(pin) => {
+                api.speed_limit_clear_pin(&self.id, &pin).await?;
+            }
+            VehicleCommand::AdjustVolume(args) => {
+                let media_info = vehicle_state(api, &self.id)
+                    .await?
+                    .media_info
+                    .ok_or_else(|| miette!("No media info returned."))?;
+                let volume = AdjustVolume::new(args.volume, &media_info)?;
+                api.adjust_volume(&self.id, &volume).await?;
+            }
+            VehicleCommand::MediaTogglePlayback => {
+                api.media_toggle_playback(&self.id).await?;
+            }
+            VehicleCommand::MediaNextTrack => {
+                api.media_next_track(&self.id).await?;
+            }
+            VehicleCommand::MediaPrevTrack => {
+                api.media_prev_track(&self.id).await?;
+            }
+            VehicleCommand::MediaNextFav => {
+                api.media_next_fav(&self.id).await?;
+            }
+            VehicleCommand::MediaPrevFav => {
+                api.media_prev_fav(&self.id).await?;
+            }
+            VehicleCommand::MediaVolumeUp => {
+                api.media_volume_up(&self.id).await?;
+            }
+            VehicleCommand::MediaVolumeDown => {
+                api.media_volume_down(&self.id).await?;
+            }
+            VehicleCommand::Navigate(args) => match args.destination {
+                Destination::Address(address) => {
+                    api.navigation_request(&self.id, &NavigationRequest::new(address))
+                        .await?;
+                }
+                Destination::Coordinates { lat, lon } => {
+                    let gps = NavigationGpsRequest {
+                        lat,
+                        lon,
+                        order: args.order,
+                    };
+                    api.navigation_gps_request(&self.id, &gps).await?;
+                }
+            },
+            VehicleCommand::NavigateSupercharger(supercharger) => {
+                api.navigation_sc_request(&self.id, &supercharger).await?;
+            }
+            VehicleCommand::NavigateWaypoints { place_ids } => {
+                let waypoints = NavigationWaypointsRequest::new(&place_ids);
+                api.navigation_waypoints_request(&self.id, &waypoints)
+                    .await?;
+            }
+            VehicleCommand::ScheduleSoftwareUpdate(schedule) => {
+                api.schedule_software_update(&self.id, &schedule).await?;
+            }
+            VehicleCommand::CancelSoftwareUpdate => {
+                api.cancel_software_update(&self.id).await?;
+            }
+            VehicleCommand::WaitSoftwareUpdate { interval, timeout } => {
+                let software_update = api
+                    .wait_for_software_update(
+                        &self.id,
+                        Duration::from_secs(interval),
+                        Duration::from_secs(timeout * 60),
+                        |update| {
+                            println!(
+                                "{} {}: downloaded {}%, installed {}%",
+                                update.status,
+                                update.version,
+                                update.download_perc,
+                                update.install_perc
+                            );
+                        },
+                    )
+                    .await?;
+                println!(
+                    "Finished: {} {}",
+                    software_update.status, software_update.version
+                );
+            }
+            VehicleCommand::TriggerHomelink(homelink) => {
+                api.trigger_homelink(&self.id, &homelink).await?;
+            }
+            VehicleCommand::RemoteBoombox(boombox) => {
+                api.remote_boombox(&self.id, &boombox).await?;
+            }
+            VehicleCommand::SetVehicleName(name) => {
+                api.set_vehicle_name(&self.id, &name).await?;
+            }
+            VehicleCommand::Sharing(sharing) => {
+                sharing.run(api, &self.id).await?;
+            }
+            VehicleCommand::EraseUserData { yes } => {
+                if !yes {
+                    return Err(miette!("Pass --yes to erase the user data."));
+                }
+                api.erase_user_data(&self.id).await?;
+            }
         }
         Ok(())
     }
 }
+
+async fn print_monitor(api: &OwnerApi, id: VehicleId) -> miette::Result<()> {
+    use crate::monitor::{MonitorConfig, VehicleMonitor};
+    use std::sync::Arc;
+
+    // The snapshots are summarised below instead.
+    let api = OwnerApi {
+        print_responses: PrintResponses::No,
+        ..api.clone()
+    };
+    let mut monitor = VehicleMonitor::spawn(Arc::new(api), id, MonitorConfig::default());
+    while let Some(snapshot) = monitor.recv().await {
+        let snapshot = match snapshot {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
+        let data = snapshot.data.as_ref();
+        let shift_state = data
+            .and_then(|d| d.drive_state.as_ref())
+            .and_then(|d| d.shift_state.clone());
+        let charge = data.and_then(|d| d.charge_state.as_ref());
+        println!(
+            "{} {} {} {} {}",
+            snapshot.time.to_rfc3339(),
+            snapshot.state,
+            shift_state.unwrap_or_else(|| "-".to_string()),
+            charge.map_or("-".to_string(), |c| c.charging_state.clone()),
+            charge.map_or("-".to_string(), |c| format!("{}%", c.battery_level)),
+        );
+    }
+    Ok(())
+}
+
+async fn print_events(api: &OwnerApi, id: VehicleId) -> miette::Result<()> {
+    use crate::monitor::{MonitorConfig, VehicleMonitor};
+    use futures_util::StreamExt;
+    use std::sync::Arc;
+
+    let api = OwnerApi {
+        print_responses: PrintResponses::No,
+        ..api.clone()
+    };
+    let monitor = VehicleMonitor::spawn(Arc::new(api), id, MonitorConfig::default());
+    let mut events = Box::pin(monitor.events());
+    while let Some(event) = events.next().await {
+        match event {
+            Ok(event) => println!("{} {event:?}", chrono::Utc::now().to_rfc3339()),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "streaming")]
+async fn print_stream(api: &OwnerApi, id: &VehicleId) -> miette::Result<()> {
+    use crate::streaming::StreamEvent;
+    use futures_util::StreamExt;
+
+    fn show<T: ToString>(value: Option<T>) -> String {
+        value
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    let mut stream = api.stream_vehicle(id).await?;
+    while let Some(event) = stream.next().await {
+        match event {
+            StreamEvent::Data(data) => println!(
+                "{} {} mph, {}%, {} kW, {}, {},{}",
+                data.timestamp.to_rfc3339(),
+                show(data.speed),
+                show(data.soc),
+                show(data.power),
+                show(data.shift_state),
+                show(data.est_lat),
+                show(data.est_lng),
+            ),
+            StreamEvent::Disconnected(disconnect) => {
+                eprintln!("Disconnected: {disconnect:?}")
+            }
+        }
+    }
+    Err(miette!(
+        "The streaming service didn't accept the access token. Run `teslatte refresh` and try again."
+    ))
+}
+
+async fn vehicle_state(api: &OwnerApi, id: &VehicleId) -> miette::Result<VehicleState> {
+    let get_vehicle_data =
+        GetVehicleData::new_with_endpoints(id.clone(), vec![Endpoint::VehicleState]);
+    api.vehicle_data(&get_vehicle_data)
+        .await?
+        .vehicle_state
+        .ok_or_else(|| miette!("No vehicle state returned."))
+}
+
+fn print_nearby_charging_sites(sites: &NearbyChargingSites, only_available: bool) {
+    let rows = sites
+        .by_distance()
+        .into_iter()
+        .filter(|site| !only_available || site.is_available())
+        .map(|site| {
+            let (kind, stalls) = match site {
+                NearbyChargingSite::Supercharger(s) if s.site_closed => {
+                    ("supercharger", "closed".to_string())
+                }
+                NearbyChargingSite::Supercharger(s) => (
+                    "supercharger",
+                    format!("{}/{}", s.available_stalls, s.total_stalls),
+                ),
+                NearbyChargingSite::Destination(_) => ("destination", String::new()),
+            };
+            [
+                format!("{:.1}", site.distance_miles()),
+                kind.to_string(),
+                stalls,
+                site.name().to_string(),
+            ]
+        })
+        .collect();
+
+    print_table(["MILES", "TYPE", "STALLS", "NAME"], rows);
+}
@@ -0,0 +1,57 @@
+use crate::OwnerApi;
+use clap::{Args, Subcommand};
+
+#[derive(Debug, Subcommand)]
+pub enum VinCommand {
+    /// Specs of the vehicle.
+    Specs,
+
+    /// Option codes of the vehicle.
+    Options,
+
+    /// Whether the vehicle is paired with the application's key, and its firmware.
+    FleetStatus,
+
+    /// Upgrades that can be bought for the vehicle.
+    EligibleUpgrades,
+
+    /// Subscriptions that can be bought for the vehicle.
+    EligibleSubscriptions,
+
+    /// Active, upcoming and expired warranties.
+    Warranty,
+}
+
+#[derive(Debug, Args)]
+pub struct VinArgs {
+    pub vin: String,
+
+    #[clap(subcommand)]
+    pub command: VinCommand,
+}
+
+impl VinArgs {
+    pub async fn run(&self, api: &OwnerApi) -> miette::Result<()> {
+        match self.command {
+            VinCommand::Specs => {
+                api.vehicle_specs(&self.vin).await?;
+            }
+            VinCommand::Options => {
+                api.vehicle_options(&self.vin).await?;
+            }
+            VinCommand::FleetStatus => {
+                api.fleet_status(std::slice::from_ref(&self.vin)).await?;
+            }
+            VinCommand::EligibleUpgrades => {
+                api.eligible_upgrades(&self.vin).await?;
+            }
+            VinCommand::EligibleSubscriptions => {
+                api.eligible_subscriptions(&self.vin).await?;
+            }
+            VinCommand::Warranty => {
+                api.warranty_details(&self.vin).await?;
+            }
+        }
+        Ok(())
+    }
+}
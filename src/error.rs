@@ -1,3 +1,7 @@
+use crate::scopes::Scope;
+use crate::vehicles::{OnlineState, Seat};
+use crate::VehicleId;
+
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum TeslatteError {
     #[error("{request} server error: {msg}: {description:?}")]
@@ -48,6 +52,85 @@ pub enum TeslatteError {
     #[error("Could not convert \"{0}\" to an EnergySiteId.")]
     DecodeEnergySiteIdError(String),
 
+    #[error("Could not convert \"{0}\" to days of the week.")]
+    DecodeDaysOfWeekError(String),
+
     #[error("No refresh token available.")]
     NoRefreshToken,
+
+    #[error("{endpoint} requires the {scope} scope, which the access token doesn't have.")]
+    MissingScope { endpoint: String, scope: Scope },
+
+    #[error("Vehicle {vehicle_id} is {state}.")]
+    VehicleNotOnline {
+        vehicle_id: VehicleId,
+        state: OnlineState,
+    },
+
+    #[error("The location of vehicle {vehicle_id} is not known.")]
+    VehicleLocationUnknown { vehicle_id: VehicleId },
+
+    #[error("The config of vehicle {vehicle_id} is not known.")]
+    VehicleConfigUnknown { vehicle_id: VehicleId },
+
+    #[error("A PIN must be 4 digits.")]
+    InvalidPin,
+
+    #[error("Speed limit of {limit_mph:.1} mph is outside of {min_mph:.1} to {max_mph:.1} mph.")]
+    SpeedLimitOutOfRange {
+        limit_mph: f64,
+        min_mph: f64,
+        max_mph: f64,
+    },
+
+    #[error("Volume of {volume:.1} is outside of 0.0 to {max:.1}.")]
+    VolumeOutOfRange { volume: f64, max: f64 },
+
+    #[error("Seat {feature} is not supported for the {seat} seat of this vehicle.")]
+    SeatFeatureNotSupported { seat: Seat, feature: &'static str },
+
+    #[error("Vehicle {vehicle_id} is not near a HomeLink device.")]
+    HomelinkNotNearby { vehicle_id: VehicleId },
+
+    #[error("This vehicle doesn't accept navigation requests.")]
+    NavigationNotSupported,
+
+    #[error("The software update of vehicle {vehicle_id} didn't finish within {timeout:?}.")]
+    SoftwareUpdateTimeout {
+        vehicle_id: VehicleId,
+        timeout: std::time::Duration,
+    },
+
+    #[error("Could not decode the access token: {0}")]
+    DecodeAccessTokenError(String),
+
+    #[cfg(feature = "telemetry")]
+    #[error("Could not decode a Fleet Telemetry record.")]
+    DecodeTelemetryError(#[source] prost::DecodeError),
+
+    #[error("Could not decode a Fleet Telemetry message: {0}")]
+    DecodeTelemetryMessageError(String),
+
+    #[error("Unknown Fleet Telemetry topic: {0}")]
+    UnknownTelemetryTopic(String),
+
+    #[error("Fleet Telemetry TLS config error: {0}")]
+    TelemetryTlsError(String),
+
+    #[error("Fleet Telemetry server IO error.")]
+    TelemetryIoError(#[source] std::io::Error),
+
+    #[error("Could not decode streaming data: {0}")]
+    DecodeStreamDataError(String),
+
+    #[error("Streaming TLS config error: {0}")]
+    StreamingTlsError(String),
+}
+
+impl TeslatteError {
+    /// The API responded with `408 Request Timeout` and `vehicle unavailable`, which it does
+    /// when the vehicle is asleep or offline.
+    pub fn is_vehicle_unavailable(&self) -> bool {
+        matches!(self, TeslatteError::ServerError { msg, .. } if msg.starts_with("vehicle unavailable"))
+    }
 }
@@ -0,0 +1,388 @@
+//! Configure vehicles to stream data to a Fleet Telemetry server, instead of polling them.
+use crate::error::TeslatteError;
+use crate::{check_scope, pub_get_arg, OwnerApi};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+#[rustfmt::skip]
+impl OwnerApi {
+    pub_get_arg!(fleet_telemetry_config, FleetTelemetryConfigStatus, "/vehicles/{}/fleet_telemetry_config", str, [VehicleDeviceData]);
+    pub_get_arg!(fleet_telemetry_errors, FleetTelemetryErrors, "/vehicles/{}/fleet_telemetry_errors", str, [VehicleDeviceData]);
+}
+
+impl OwnerApi {
+    /// Send the config to the vehicles. Vehicles that can't use it are listed in
+    /// [FleetTelemetryConfigCreated::skipped_vehicles].
+    pub async fn fleet_telemetry_config_create(
+        &self,
+        request: &FleetTelemetryConfigRequest,
+    ) -> Result<FleetTelemetryConfigCreated, TeslatteError> {
+        check_scope!(self, fleet_telemetry_config_create, [VehicleDeviceData])?;
+        let url = format!("{}/vehicles/fleet_telemetry_config", self.api_url);
+        self.post_data(&url, request).await
+    }
+
+    /// Stop the vehicle streaming to the Fleet Telemetry server.
+    pub async fn fleet_telemetry_config_delete(
+        &self,
+        vin: &str,
+    ) -> Result<FleetTelemetryConfigDeleted, TeslatteError> {
+        check_scope!(self, fleet_telemetry_config_delete, [VehicleDeviceData])?;
+        let url = format!("{}/vehicles/{}/fleet_telemetry_config", self.api_url, vin);
+        self.delete(&url).await
+    }
+}
+
+/// Names of the fields a vehicle can stream. See the Fleet Telemetry `vehicle_data.proto` for
+/// what each one contains.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString, EnumIter,
+)]
+pub enum TelemetryField {
+    ACChargingEnergyIn,
+    ACChargingPower,
+    BatteryHeaterOn,
+    BatteryLevel,
+    BrakePedal,
+    ChargeAmps,
+    ChargeLimitSoc,
+    ChargePortDoorOpen,
+    ChargeState,
+    ChargerPhases,
+    ChargerVoltage,
+    CruiseSetSpeed,
+    DCChargingEnergyIn,
+    DCChargingPower,
+    DestinationLocation,
+    DestinationName,
+    DetailedChargeState,
+    DoorState,
+    EnergyRemaining,
+    EstBatteryRange,
+    FastChargerPresent,
+    FdWindow,
+    FpWindow,
+    Gear,
+    GpsHeading,
+    GpsState,
+    HvacACEnabled,
+    HvacPower,
+    IdealBatteryRange,
+    InsideTemp,
+    LateralAcceleration,
+    Location,
+    Locked,
+    LongitudinalAcceleration,
+    MilesToArrival,
+    MinutesToArrival,
+    ModuleTempMax,
+    ModuleTempMin,
+    Odometer,
+    OriginLocation,
+    OutsideTemp,
+    PackCurrent,
+    PackVoltage,
+    PedalPosition,
+    RatedRange,
+    RdWindow,
+    RpWindow,
+    ScheduledChargingStartTime,
+    SentryMode,
+    Soc,
+    TimeToFullCharge,
+    TpmsPressureFl,
+    TpmsPressureFr,
+    TpmsPressureRl,
+    TpmsPressureRr,
+    VehicleName,
+    VehicleSpeed,
+    Version,
+}
+
+impl TelemetryField {
+    /// The number of the field in the `Field` enum of `vehicle_data.proto`.
+    pub fn number(self) -> i32 {
+        match self {
+            TelemetryField::ChargeState => 2,
+            TelemetryField::VehicleSpeed => 4,
+            TelemetryField::Odometer => 5,
+            TelemetryField::PackVoltage => 6,
+            TelemetryField::PackCurrent => 7,
+            TelemetryField::Soc => 8,
+            TelemetryField::Gear => 10,
+            TelemetryField::PedalPosition => 12,
+            TelemetryField::BrakePedal => 13,
+            TelemetryField::Location => 21,
+            TelemetryField::GpsState => 22,
+            TelemetryField::GpsHeading => 23,
+            TelemetryField::ModuleTempMax => 29,
+            TelemetryField::ModuleTempMin => 31,
+            TelemetryField::RatedRange => 32,
+            TelemetryField::DCChargingEnergyIn => 34,
+            TelemetryField::DCChargingPower => 35,
+            TelemetryField::ACChargingEnergyIn => 36,
+            TelemetryField::ACChargingPower => 37,
+            TelemetryField::ChargeLimitSoc => 38,
+            TelemetryField::FastChargerPresent => 39,
+            TelemetryField::EstBatteryRange => 40,
+            TelemetryField::IdealBatteryRange => 41,
+            TelemetryField::BatteryLevel => 42,
+            TelemetryField::TimeToFullCharge => 43,
+            TelemetryField::ScheduledChargingStartTime => 44,
+            TelemetryField::ChargeAmps => 49,
+            TelemetryField::ChargerPhases => 51,
+            TelemetryField::BatteryHeaterOn => 55,
+            TelemetryField::DoorState => 58,
+            TelemetryField::Locked => 59,
+            TelemetryField::FdWindow => 60,
+            TelemetryField::FpWindow => 61,
+            TelemetryField::RdWindow => 62,
+            TelemetryField::RpWindow => 63,
+            TelemetryField::VehicleName => 64,
+            TelemetryField::SentryMode => 65,
+            TelemetryField::Version => 68,
+            TelemetryField::TpmsPressureFl => 69,
+            TelemetryField::TpmsPressureFr => 70,
+            TelemetryField::TpmsPressureRl => 71,
+            TelemetryField::TpmsPressureRr => 72,
+            TelemetryField::InsideTemp => 85,
+            TelemetryField::OutsideTemp => 86,
+            TelemetryField::LateralAcceleration => 98,
+            TelemetryField::LongitudinalAcceleration => 99,
+            TelemetryField::CruiseSetSpeed => 101,
+            TelemetryField::MilesToArrival => 109,
+            TelemetryField::MinutesToArrival => 110,
+            TelemetryField::OriginLocation => 111,
+            TelemetryField::DestinationLocation => 112,
+            TelemetryField::EnergyRemaining => 158,
+            TelemetryField::DestinationName => 163,
+            TelemetryField::DetailedChargeState => 179,
+            TelemetryField::ChargePortDoorOpen => 183,
+            TelemetryField::ChargerVoltage => 184,
+            TelemetryField::HvacACEnabled => 196,
+            TelemetryField::HvacPower => 201,
+        }
+    }
+
+    pub fn from_number(number: i32) -> Option<Self> {
+        Self::iter().find(|field| field.number() == number)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TelemetryAlertType {
+    Service,
+    Customer,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryFieldConfig {
+    pub interval_seconds: u32,
+    /// Only send the field when it changed by at least this much.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum_delta: Option<f64>,
+    /// Send the field again after this long, even if it didn't change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resend_interval_seconds: Option<u32>,
+}
+
+/// Where a vehicle streams to, and what it sends.
+///
+/// ```rust
+/// # use teslatte::fleet_telemetry::{FleetTelemetryConfig, TelemetryAlertType, TelemetryField};
+/// # use chrono::{TimeDelta, Utc};
+/// let config = FleetTelemetryConfig::new(
+///     "telemetry.example.com",
+///     "-----BEGIN CERTIFICATE-----\n...",
+///     Utc::now() + TimeDelta::days(30),
+/// )
+/// .field(TelemetryField::BatteryLevel, 60)
+/// .field(TelemetryField::Location, 10)
+/// .alert_type(TelemetryAlertType::Service);
+/// assert_eq!(config.fields["BatteryLevel"].interval_seconds, 60);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FleetTelemetryConfig {
+    pub hostname: String,
+    /// PEM certificate chain of the server's certificate authority.
+    pub ca: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// By field name. Use [FleetTelemetryConfig::field] to add the fields.
+    #[serde(default)]
+    pub fields: BTreeMap<String, TelemetryFieldConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alert_types: Vec<TelemetryAlertType>,
+    /// The vehicle stops streaming after this.
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub exp: DateTime<Utc>,
+}
+
+impl FleetTelemetryConfig {
+    pub fn new(hostname: impl Into<String>, ca: impl Into<String>, exp: DateTime<Utc>) -> Self {
+        Self {
+            hostname: hostname.into(),
+            ca: ca.into(),
+            port: None,
+            fields: BTreeMap::new(),
+            alert_types: vec![],
+            exp,
+        }
+    }
+
+    /// Send the field every `interval_seconds`.
+    pub fn field(self, field: TelemetryField, interval_seconds: u32) -> Self {
+        self.field_config(
+            field,
+            TelemetryFieldConfig {
+                interval_seconds,
+                minimum_delta: None,
+                resend_interval_seconds: None,
+            },
+        )
+    }
+
+    pub fn field_config(mut self, field: TelemetryField, config: TelemetryFieldConfig) -> Self {
+        self.fields.insert(field.to_string(), config);
+        self
+    }
+
+    pub fn alert_type(mut self, alert_type: TelemetryAlertType) -> Self {
+        if !self.alert_types.contains(&alert_type) {
+            self.alert_types.push(alert_type);
+        }
+        self
+    }
+
+    /// Defaults to 443.
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+}
+
+/// fleet_telemetry_config_create
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetTelemetryConfigRequest {
+    pub vins: Vec<String>,
+    pub config: FleetTelemetryConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetTelemetryConfigCreated {
+    pub updated_vehicles: i64,
+    pub skipped_vehicles: Option<SkippedVehicles>,
+}
+
+/// VINs of vehicles that weren't configured, by reason.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedVehicles {
+    /// The vehicle doesn't have the application's virtual key.
+    #[serde(default)]
+    pub missing_key: Vec<String>,
+    #[serde(default)]
+    pub unsupported_hardware: Vec<String>,
+    #[serde(default)]
+    pub unsupported_firmware: Vec<String>,
+    /// The vehicle already streams to the maximum number of applications.
+    #[serde(default)]
+    pub max_configs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetTelemetryConfigStatus {
+    /// Whether the vehicle has received the config.
+    pub synced: bool,
+    pub config: Option<FleetTelemetryConfig>,
+    pub key_paired: Option<bool>,
+    pub limit_reached: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetTelemetryConfigDeleted {
+    pub updated_vehicles: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetTelemetryErrors {
+    pub fleet_telemetry_errors: Vec<FleetTelemetryError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetTelemetryError {
+    pub name: String,
+    pub error: String,
+    pub vin: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PrintResponses, RequestData};
+
+    #[test]
+    fn config_request() {
+        let exp = DateTime::from_timestamp(1704067200, 0).unwrap();
+        let config = FleetTelemetryConfig::new("telemetry.example.com", "CA", exp)
+            .field(TelemetryField::Soc, 60)
+            .field(TelemetryField::ACChargingPower, 30)
+            .alert_type(TelemetryAlertType::Service)
+            .alert_type(TelemetryAlertType::Service);
+        let request = FleetTelemetryConfigRequest {
+            vins: vec!["5YJ3000000NEXUS01".to_string()],
+            config,
+        };
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"vins":["5YJ3000000NEXUS01"],"config":{"hostname":"telemetry.example.com","ca":"CA","fields":{"ACChargingPower":{"interval_seconds":30},"Soc":{"interval_seconds":60}},"alert_types":["service"],"exp":1704067200}}"#
+        );
+        assert_eq!("VehicleSpeed".parse(), Ok(TelemetryField::VehicleSpeed));
+        assert!("vehicle_speed".parse::<TelemetryField>().is_err());
+    }
+
+    #[test]
+    fn field_numbers() {
+        for field in TelemetryField::iter() {
+            assert_eq!(TelemetryField::from_number(field.number()), Some(field));
+            assert_eq!(field.to_string().parse(), Ok(field));
+        }
+        assert_eq!(TelemetryField::from_number(0), None);
+    }
+
+    #[test]
+    fn json_config_status() {
+        let s = r#"{
+          "response": {
+            "synced": true,
+            "config": {
+              "hostname": "telemetry.example.com",
+              "ca": "-----BEGIN CERTIFICATE-----",
+              "exp": 1704067200,
+              "port": 4443,
+              "fields": {
+                "BatteryLevel": { "interval_seconds": 60 },
+                "SomeNewField": { "interval_seconds": 1800 }
+              },
+              "alert_types": ["service"]
+            },
+            "limit_reached": false,
+            "key_paired": true
+          }
+        }"#;
+        let request_data = RequestData::Get { url: "" };
+        let status = OwnerApi::parse_json::<FleetTelemetryConfigStatus>(
+            &request_data,
+            s.to_string(),
+            PrintResponses::No,
+        )
+        .unwrap();
+        let config = status.config.unwrap();
+        assert_eq!(config.port, Some(4443));
+        assert_eq!(config.fields.len(), 2);
+        assert_eq!(config.exp.timestamp(), 1704067200);
+    }
+}
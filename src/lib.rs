@@ -2,11 +2,20 @@
 // TODO: Maybe use the suggestion of removing async and replacing it with Future<Output = Result<...>>
 #![allow(async_fn_in_trait)]
 
-use crate::auth::{AccessToken, RefreshToken};
+use crate::auth::{AccessToken, AuthConfig, RefreshToken};
 use crate::error::TeslatteError;
+use crate::scopes::Scope;
 use crate::vehicles::{
-    GetVehicleData, SetChargeLimit, SetChargingAmps, SetScheduledCharging, SetScheduledDeparture,
-    SetTemperatures, VehicleData,
+    ActuateTrunk, AddChargeSchedule, AddPreconditionSchedule, AdjustVolume, GetVehicleData,
+    NavigationGpsRequest, NavigationRequest, NavigationScRequest, NavigationWaypointsRequest,
+    NearbyChargingSites, NearbyChargingSitesValues, RemoteAutoSeatClimate,
+    RemoteAutoSteeringWheelHeat, RemoteBoombox, RemoteSeatCooler, RemoteSeatHeater,
+    RemoteSteeringWheelHeatLevel, RemoteSteeringWheelHeater, RemoveSchedule,
+    ScheduleSoftwareUpdate, SetBioweaponMode, SetCabinOverheatProtection, SetChargeLimit,
+    SetChargingAmps, SetClimateKeeperMode, SetCopTemp, SetPinToDrive, SetPreconditioningMax,
+    SetScheduledCharging, SetScheduledDeparture, SetSentryMode, SetTemperatures, SetValetMode,
+    SetVehicleName, SpeedLimitPin, SpeedLimitSetLimit, SunRoofControl, TriggerHomelink, Vehicle,
+    VehicleData, WindowControl,
 };
 use chrono::{DateTime, SecondsFormat, TimeZone};
 use derive_more::{Deref, Display, From, FromStr};
@@ -16,23 +25,39 @@ use std::fmt::{Debug, Display};
 use tracing::debug;
 
 pub mod auth;
+pub mod charging;
 pub mod energy_sites;
 pub mod error;
+pub mod fleet_telemetry;
+pub mod monitor;
 pub mod powerwall;
 pub mod products;
+pub mod redact;
+pub mod scopes;
+pub mod sharing;
+#[cfg(feature = "streaming")]
+pub mod streaming;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+pub mod vehicle_info;
+pub mod vehicle_status;
 pub mod vehicles;
 
 #[cfg(feature = "cli")]
 pub mod cli;
 
-const API_URL: &str = "https://owner-api.teslamotors.com/api/1";
-
 pub trait VehicleApi {
+    /// Summary of a vehicle, including whether it is online. This does not wake up the vehicle.
+    async fn vehicle(&self, vehicle_id: &VehicleId) -> Result<Vehicle, TeslatteError>;
     async fn vehicle_data(
         &self,
         get_vehicle_data: &GetVehicleData,
     ) -> Result<VehicleData, TeslatteError>;
     async fn wake_up(&self, vehicle_id: &VehicleId) -> Result<PostResponse, TeslatteError>;
+    async fn nearby_charging_sites(
+        &self,
+        values: &NearbyChargingSitesValues,
+    ) -> Result<NearbyChargingSites, TeslatteError>;
 
     // Alerts
     async fn honk_horn(&self, vehicle_id: &VehicleId) -> Result<PostResponse, TeslatteError>;
@@ -72,6 +97,26 @@ pub trait VehicleApi {
         vehicle_id: &VehicleId,
         data: &SetScheduledDeparture,
     ) -> Result<PostResponse, TeslatteError>;
+    async fn add_charge_schedule(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &AddChargeSchedule,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn remove_charge_schedule(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &RemoveSchedule,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn add_precondition_schedule(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &AddPreconditionSchedule,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn remove_precondition_schedule(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &RemoveSchedule,
+    ) -> Result<PostResponse, TeslatteError>;
 
     // HVAC
     async fn auto_conditioning_start(
@@ -87,6 +132,65 @@ pub trait VehicleApi {
         vehicle_id: &VehicleId,
         data: &SetTemperatures,
     ) -> Result<PostResponse, TeslatteError>;
+    /// Fails with [TeslatteError::SeatFeatureNotSupported] if the vehicle has no heater in the
+    /// seat. See [Seat::check_heater](vehicles::Seat::check_heater).
+    async fn remote_seat_heater_request(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &RemoteSeatHeater,
+    ) -> Result<PostResponse, TeslatteError>;
+    /// Fails with [TeslatteError::SeatFeatureNotSupported] if the vehicle has no cooling in the
+    /// seat. See [Seat::check_cooler](vehicles::Seat::check_cooler).
+    async fn remote_seat_cooler_request(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &RemoteSeatCooler,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn remote_auto_seat_climate_request(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &RemoteAutoSeatClimate,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn remote_steering_wheel_heater_request(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &RemoteSteeringWheelHeater,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn remote_steering_wheel_heat_level_request(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &RemoteSteeringWheelHeatLevel,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn remote_auto_steering_wheel_heat_climate_request(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &RemoteAutoSteeringWheelHeat,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn set_climate_keeper_mode(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SetClimateKeeperMode,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn set_cabin_overheat_protection(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SetCabinOverheatProtection,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn set_cop_temp(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SetCopTemp,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn set_bioweapon_mode(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SetBioweaponMode,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn set_preconditioning_max(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SetPreconditioningMax,
+    ) -> Result<PostResponse, TeslatteError>;
 
     // Doors
     async fn door_unlock(&self, vehicle_id: &VehicleId) -> Result<PostResponse, TeslatteError>;
@@ -95,6 +199,148 @@ pub trait VehicleApi {
         &self,
         vehicle_id: &VehicleId,
     ) -> Result<PostResponse, TeslatteError>;
+    async fn actuate_trunk(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &ActuateTrunk,
+    ) -> Result<PostResponse, TeslatteError>;
+
+    // Windows
+    /// Closing the windows needs the location of the vehicle. If it isn't in `data`, it is
+    /// fetched from [DriveState](vehicles::DriveState) first.
+    async fn window_control(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &WindowControl,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn sun_roof_control(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SunRoofControl,
+    ) -> Result<PostResponse, TeslatteError>;
+
+    // Security
+    async fn set_sentry_mode(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SetSentryMode,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn set_valet_mode(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SetValetMode,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn reset_valet_pin(&self, vehicle_id: &VehicleId) -> Result<PostResponse, TeslatteError>;
+    async fn set_pin_to_drive(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SetPinToDrive,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn reset_pin_to_drive_pin(
+        &self,
+        vehicle_id: &VehicleId,
+    ) -> Result<PostResponse, TeslatteError>;
+
+    // Speed limit
+    async fn speed_limit_activate(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SpeedLimitPin,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn speed_limit_deactivate(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SpeedLimitPin,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn speed_limit_set_limit(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SpeedLimitSetLimit,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn speed_limit_clear_pin(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SpeedLimitPin,
+    ) -> Result<PostResponse, TeslatteError>;
+
+    // Media
+    async fn adjust_volume(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &AdjustVolume,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn media_toggle_playback(
+        &self,
+        vehicle_id: &VehicleId,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn media_next_track(&self, vehicle_id: &VehicleId)
+        -> Result<PostResponse, TeslatteError>;
+    async fn media_prev_track(&self, vehicle_id: &VehicleId)
+        -> Result<PostResponse, TeslatteError>;
+    async fn media_next_fav(&self, vehicle_id: &VehicleId) -> Result<PostResponse, TeslatteError>;
+    async fn media_prev_fav(&self, vehicle_id: &VehicleId) -> Result<PostResponse, TeslatteError>;
+    async fn media_volume_up(&self, vehicle_id: &VehicleId) -> Result<PostResponse, TeslatteError>;
+    async fn media_volume_down(
+        &self,
+        vehicle_id: &VehicleId,
+    ) -> Result<PostResponse, TeslatteError>;
+
+    // Navigation
+    // These fail with `TeslatteError::NavigationNotSupported` if the vehicle doesn't accept
+    // destinations.
+    async fn navigation_request(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &NavigationRequest,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn navigation_gps_request(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &NavigationGpsRequest,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn navigation_sc_request(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &NavigationScRequest,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn navigation_waypoints_request(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &NavigationWaypointsRequest,
+    ) -> Result<PostResponse, TeslatteError>;
+
+    // Software update
+    async fn schedule_software_update(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &ScheduleSoftwareUpdate,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn cancel_software_update(
+        &self,
+        vehicle_id: &VehicleId,
+    ) -> Result<PostResponse, TeslatteError>;
+
+    // Misc
+    /// Fails with [TeslatteError::HomelinkNotNearby] unless the vehicle reports
+    /// [VehicleState::homelink_nearby](vehicles::VehicleState::homelink_nearby). The location is
+    /// fetched from [DriveState](vehicles::DriveState) if it isn't in `data`.
+    async fn trigger_homelink(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &TriggerHomelink,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn remote_boombox(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &RemoteBoombox,
+    ) -> Result<PostResponse, TeslatteError>;
+    async fn set_vehicle_name(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &SetVehicleName,
+    ) -> Result<PostResponse, TeslatteError>;
+    /// Erases the user data on the vehicle's touchscreen.
+    async fn erase_user_data(&self, vehicle_id: &VehicleId) -> Result<PostResponse, TeslatteError>;
 }
 
 trait ApiValues {
@@ -122,13 +368,22 @@ pub struct ExternalVehicleId(u64);
 enum RequestData<'a> {
     Get { url: &'a str },
     Post { url: &'a str, payload: &'a str },
+    Delete { url: &'a str },
 }
 
+/// Shown in logs and errors, so VINs and sensitive values in the payload are redacted. See
+/// [redact].
 impl Display for RequestData<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            RequestData::Get { url } => write!(f, "GET {}", url),
-            RequestData::Post { url, payload } => write!(f, "POST {} {}", url, payload),
+            RequestData::Get { url } => write!(f, "GET {}", redact::redact_vins(url)),
+            RequestData::Post { url, payload } => write!(
+                f,
+                "POST {} {}",
+                redact::redact_vins(url),
+                redact::redact(payload)
+            ),
+            RequestData::Delete { url } => write!(f, "DELETE {}", redact::redact_vins(url)),
         }
     }
 }
@@ -144,23 +399,81 @@ pub enum PrintResponses {
 ///
 /// Main entry point for the API. It contains the access token and refresh token, and can be used
 /// to make requests to the API.
+#[derive(Clone)]
 pub struct OwnerApi {
     pub access_token: AccessToken,
     pub refresh_token: Option<RefreshToken>,
     pub print_responses: PrintResponses,
+    /// Scopes granted to the access token. When set, requests to endpoints that need a scope
+    /// that isn't in this list fail with [TeslatteError::MissingScope] without calling the API.
+    ///
+    /// This is filled in from Fleet API access tokens. See [scopes](crate::scopes).
+    pub scopes: Option<Vec<Scope>>,
+    /// Base URL of the API, including `/api/1`.
+    pub api_url: String,
+    /// Used to refresh the access token.
+    pub auth_config: AuthConfig,
     client: Client,
 }
 
 impl OwnerApi {
     pub fn new(access_token: AccessToken, refresh_token: Option<RefreshToken>) -> Self {
-        Self {
-            access_token,
+        Self::new_with_auth_config(AuthConfig::default(), access_token, refresh_token)
+    }
+
+    /// Create a client for a specific [AuthConfig], e.g. for accounts in China.
+    ///
+    /// If the access token was issued for the Fleet API, its audience is used as the API base URL
+    /// instead of [AuthConfig::api_url].
+    pub fn new_with_auth_config(
+        auth_config: AuthConfig,
+        access_token: AccessToken,
+        refresh_token: Option<RefreshToken>,
+    ) -> Self {
+        let mut api = Self {
+            access_token: AccessToken::new(""),
             refresh_token,
             print_responses: PrintResponses::No,
+            scopes: None,
+            api_url: String::new(),
+            auth_config,
             client: Client::builder()
                 .timeout(std::time::Duration::from_secs(10))
                 .build()
                 .unwrap(), // TODO: unwrap
+        };
+        api.set_access_token(access_token);
+        api
+    }
+
+    /// Replace the access token, and take [OwnerApi::scopes] and [OwnerApi::api_url] from it.
+    fn set_access_token(&mut self, access_token: AccessToken) {
+        let claims = access_token.claims().ok();
+        let fleet_api_url = claims
+            .as_ref()
+            .and_then(|claims| claims.fleet_api_audience())
+            .map(|aud| format!("{}/api/1", aud.trim_end_matches('/')));
+        self.scopes = fleet_api_url
+            .as_ref()
+            .and(claims)
+            .map(|claims| claims.scopes());
+        self.api_url = fleet_api_url.unwrap_or_else(|| self.auth_config.api_url.clone());
+        self.access_token = access_token;
+    }
+
+    /// Use the [check_scope!] macro, which makes sure the scopes are in
+    /// [ENDPOINT_SCOPES](scopes::ENDPOINT_SCOPES).
+    fn check_scope(&self, endpoint: &str, required: &[Scope]) -> Result<(), TeslatteError> {
+        let Some(granted) = &self.scopes else {
+            return Ok(());
+        };
+
+        match scopes::missing_from(required, granted) {
+            Some(scope) => Err(TeslatteError::MissingScope {
+                endpoint: endpoint.to_string(),
+                scope,
+            }),
+            None => Ok(()),
         }
     }
 
@@ -171,6 +484,32 @@ impl OwnerApi {
         self.request(&RequestData::Get { url }).await
     }
 
+    /// GET a file, e.g. a PDF, instead of JSON.
+    async fn get_bytes(&self, url: &str) -> Result<Vec<u8>, TeslatteError> {
+        let request_data = RequestData::Get { url };
+        debug!("{request_data}");
+
+        let fetch_error = |source: reqwest::Error| TeslatteError::FetchError {
+            source: source.without_url(),
+            request: format!("{request_data}"),
+        };
+        let bytes = self
+            .client
+            .get(url)
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.access_token.expose().trim()),
+            )
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(fetch_error)?
+            .bytes()
+            .await
+            .map_err(fetch_error)?;
+        Ok(bytes.to_vec())
+    }
+
     async fn post<S>(&self, url: &str, body: S) -> Result<PostResponse, TeslatteError>
     where
         S: Serialize + Debug,
@@ -184,7 +523,7 @@ impl OwnerApi {
             return Err(TeslatteError::ServerError {
                 request: format!("{request_data}"),
                 description: None,
-                msg: data.reason,
+                msg: redact::redact_vins(&data.reason),
                 body: None,
             });
         }
@@ -192,6 +531,24 @@ impl OwnerApi {
         Ok(data)
     }
 
+    /// POST for endpoints that respond with data instead of a [PostResponse].
+    async fn post_data<S, D>(&self, url: &str, body: S) -> Result<D, TeslatteError>
+    where
+        S: Serialize + Debug,
+        D: for<'de> Deserialize<'de> + Debug,
+    {
+        let payload =
+            &serde_json::to_string(&body).expect("Should not fail creating the request struct.");
+        self.request(&RequestData::Post { url, payload }).await
+    }
+
+    async fn delete<D>(&self, url: &str) -> Result<D, TeslatteError>
+    where
+        D: for<'de> Deserialize<'de> + Debug,
+    {
+        self.request(&RequestData::Delete { url }).await
+    }
+
     async fn request<T>(&self, request_data: &RequestData<'_>) -> Result<T, TeslatteError>
     where
         T: for<'de> Deserialize<'de> + Debug,
@@ -200,6 +557,7 @@ impl OwnerApi {
 
         let request_builder = match request_data {
             RequestData::Get { url } => self.client.get(*url),
+            RequestData::Delete { url } => self.client.delete(*url),
             RequestData::Post { url, payload } => self
                 .client
                 .post(*url)
@@ -211,22 +569,22 @@ impl OwnerApi {
             .header("Accept", "application/json")
             .header(
                 "Authorization",
-                format!("Bearer {}", self.access_token.0.trim()),
+                format!("Bearer {}", self.access_token.expose().trim()),
             )
             .send()
             .await
             .map_err(|source| TeslatteError::FetchError {
-                source,
+                source: source.without_url(),
                 request: format!("{request_data}"),
             })?
             .text()
             .await
             .map_err(|source| TeslatteError::FetchError {
-                source,
+                source: source.without_url(),
                 request: format!("{request_data}"),
             })?;
 
-        debug!("Response: {response_body}");
+        debug!("Response: {}", redact::redact(&response_body));
 
         Self::parse_json(request_data, response_body, self.print_responses)
     }
@@ -253,7 +611,7 @@ impl OwnerApi {
             .map_err(|source| TeslatteError::DecodeJsonError {
                 source,
                 request: format!("{request_data}"),
-                body: response_body.to_string(),
+                body: redact::redact(&response_body),
             })?
             .into();
 
@@ -261,9 +619,9 @@ impl OwnerApi {
             Response::Response(data) => Ok(data),
             Response::Error(e) => Err(TeslatteError::ServerError {
                 request: format!("{request_data}"),
-                msg: e.error,
-                description: e.error_description,
-                body: Some(response_body.to_owned()),
+                msg: redact::redact_vins(&e.error),
+                description: e.error_description.as_deref().map(redact::redact_vins),
+                body: Some(redact::redact(&response_body)),
             }),
         }
     }
@@ -271,10 +629,20 @@ impl OwnerApi {
 
 #[derive(Debug, Deserialize)]
 struct ResponseDeserializer<T> {
-    error: Option<ResponseError>,
+    error: Option<ResponseErrorField>,
+    error_description: Option<String>,
     response: Option<T>,
 }
 
+/// Some errors, e.g. `vehicle unavailable`, are a message next to `error_description` instead of
+/// an object.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ResponseErrorField {
+    Error(ResponseError),
+    Message(String),
+}
+
 #[derive(Debug)]
 enum Response<T> {
     Response(T),
@@ -284,7 +652,11 @@ enum Response<T> {
 impl<T> From<ResponseDeserializer<T>> for Response<T> {
     fn from(response: ResponseDeserializer<T>) -> Self {
         match response.error {
-            Some(error) => Response::Error(error),
+            Some(ResponseErrorField::Error(error)) => Response::Error(error),
+            Some(ResponseErrorField::Message(error)) => Response::Error(ResponseError {
+                error,
+                error_description: response.error_description,
+            }),
             None => match response.response {
                 Some(response) => Response::Response(response),
                 None => panic!("ResponseDeserializer has no error or response."),
@@ -310,12 +682,27 @@ struct ResponseError {
 #[derive(Debug, Serialize)]
 struct Empty {}
 
+/// Fail with [TeslatteError::MissingScope] unless the access token has one of the scopes, e.g.
+/// `check_scope!(self, honk_horn, [VehicleCmds])?`.
+///
+/// The endpoint has to be listed in [ENDPOINT_SCOPES](scopes::ENDPOINT_SCOPES) with the same
+/// scopes, which is checked when compiling.
+macro_rules! check_scope {
+    ($api:expr, $name:ident, [$($scope:ident),*]) => {{
+        const REQUIRED: &[crate::scopes::Scope] = &[$(crate::scopes::Scope::$scope),*];
+        const _: () = crate::scopes::assert_endpoint_scopes(stringify!($name), REQUIRED);
+        $api.check_scope(stringify!($name), REQUIRED)
+    }};
+}
+pub(crate) use check_scope;
+
 /// GET /api/1/[url]
 #[allow(unused_macros)]
 macro_rules! get {
-    ($name:ident, $return_type:ty, $url:expr) => {
+    ($name:ident, $return_type:ty, $url:expr, $scopes:tt) => {
         async fn $name(&self) -> Result<$return_type, crate::error::TeslatteError> {
-            let url = format!("{}{}", crate::API_URL, $url);
+            crate::check_scope!(self, $name, $scopes)?;
+            let url = format!("{}{}", self.api_url, $url);
             self.get(&url)
                 .await
                 .map_err(|e| crate::error::TeslatteError::from(e))
@@ -327,9 +714,10 @@ pub(crate) use get;
 
 /// Same as get, but public.
 macro_rules! pub_get {
-    ($name:ident, $return_type:ty, $url:expr) => {
+    ($name:ident, $return_type:ty, $url:expr, $scopes:tt) => {
         pub async fn $name(&self) -> Result<$return_type, crate::error::TeslatteError> {
-            let url = format!("{}{}", crate::API_URL, $url);
+            crate::check_scope!(self, $name, $scopes)?;
+            let url = format!("{}{}", self.api_url, $url);
             self.get(&url)
                 .await
                 .map_err(|e| crate::error::TeslatteError::from(e))
@@ -341,31 +729,31 @@ pub(crate) use pub_get;
 /// GET /api/1/[url] with an argument.
 ///
 /// Pass in the URL as a format string with one arg, which has to impl Display.
-#[allow(unused_macros)]
 macro_rules! get_arg {
-    ($name:ident, $return_type:ty, $url:expr, $arg_type:ty) => {
+    ($name:ident, $return_type:ty, $url:expr, $arg_type:ty, $scopes:tt) => {
         async fn $name(
             &self,
             arg: &$arg_type,
         ) -> miette::Result<$return_type, crate::error::TeslatteError> {
+            crate::check_scope!(self, $name, $scopes)?;
             let url = format!($url, arg);
-            let url = format!("{}{}", crate::API_URL, url);
+            let url = format!("{}{}", self.api_url, url);
             self.get(&url).await
         }
     };
 }
-#[allow(unused_imports)]
 pub(crate) use get_arg;
 
 /// Public variant of get_arg.
 macro_rules! pub_get_arg {
-    ($name:ident, $return_type:ty, $url:expr, $arg_type:ty) => {
+    ($name:ident, $return_type:ty, $url:expr, $arg_type:ty, $scopes:tt) => {
         pub async fn $name(
             &self,
             arg: &$arg_type,
         ) -> miette::Result<$return_type, crate::error::TeslatteError> {
+            crate::check_scope!(self, $name, $scopes)?;
             let url = format!($url, arg);
-            let url = format!("{}{}", crate::API_URL, url);
+            let url = format!("{}{}", self.api_url, url);
             self.get(&url).await
         }
     };
@@ -373,14 +761,30 @@ macro_rules! pub_get_arg {
 pub(crate) use pub_get_arg;
 
 /// GET /api/1/[url] with a struct to format the URL.
+///
+/// The optional last argument is an async function that is called with the values before the
+/// request, e.g. to check them: `async fn(&OwnerApi, &$args) -> Result<(), TeslatteError>`.
 macro_rules! get_args {
-    ($name:ident, $return_type:ty, $url:expr, $args:ty) => {
+    ($name:ident, $return_type:ty, $url:expr, $args:ty, $scopes:tt) => {
         async fn $name(
             &self,
             values: &$args,
         ) -> miette::Result<$return_type, crate::error::TeslatteError> {
+            crate::check_scope!(self, $name, $scopes)?;
             let url = values.format($url);
-            let url = format!("{}{}", crate::API_URL, url);
+            let url = format!("{}{}", self.api_url, url);
+            self.get(&url).await
+        }
+    };
+    ($name:ident, $return_type:ty, $url:expr, $args:ty, $scopes:tt, $prepare:path) => {
+        async fn $name(
+            &self,
+            values: &$args,
+        ) -> miette::Result<$return_type, crate::error::TeslatteError> {
+            crate::check_scope!(self, $name, $scopes)?;
+            $prepare(self, values).await?;
+            let url = values.format($url);
+            let url = format!("{}{}", self.api_url, url);
             self.get(&url).await
         }
     };
@@ -389,13 +793,14 @@ pub(crate) use get_args;
 
 /// Public variant of get_args.
 macro_rules! pub_get_args {
-    ($name:ident, $return_type:ty, $url:expr, $args:ty) => {
+    ($name:ident, $return_type:ty, $url:expr, $args:ty, $scopes:tt) => {
         pub async fn $name(
             &self,
             values: &$args,
         ) -> miette::Result<$return_type, crate::error::TeslatteError> {
+            crate::check_scope!(self, $name, $scopes)?;
             let url = values.format($url);
-            let url = format!("{}{}", crate::API_URL, url);
+            let url = format!("{}{}", self.api_url, url);
             self.get(&url).await
         }
     };
@@ -403,30 +808,49 @@ macro_rules! pub_get_args {
 pub(crate) use pub_get_args;
 
 /// POST /api/1/[url] with an argument and data
+///
+/// The optional last argument is an async function that is called with the argument and data
+/// before the request, and returns the data to send, e.g. after checking or completing it:
+/// `async fn(&OwnerApi, &$arg_type, &$request_type) -> Result<impl Serialize, TeslatteError>`.
 macro_rules! post_arg {
-    ($name:ident, $request_type:ty, $url:expr, $arg_type:ty) => {
+    ($name:ident, $request_type:ty, $url:expr, $arg_type:ty, $scopes:tt) => {
         async fn $name(
             &self,
             arg: &$arg_type,
             data: &$request_type,
         ) -> miette::Result<crate::PostResponse, crate::error::TeslatteError> {
+            crate::check_scope!(self, $name, $scopes)?;
             let url = format!($url, arg);
-            let url = format!("{}{}", crate::API_URL, url);
+            let url = format!("{}{}", self.api_url, url);
             self.post(&url, data).await
         }
     };
+    ($name:ident, $request_type:ty, $url:expr, $arg_type:ty, $scopes:tt, $prepare:path) => {
+        async fn $name(
+            &self,
+            arg: &$arg_type,
+            data: &$request_type,
+        ) -> miette::Result<crate::PostResponse, crate::error::TeslatteError> {
+            crate::check_scope!(self, $name, $scopes)?;
+            let data = $prepare(self, arg, data).await?;
+            let url = format!($url, arg);
+            let url = format!("{}{}", self.api_url, url);
+            self.post(&url, &data).await
+        }
+    };
 }
 pub(crate) use post_arg;
 
 /// Post like above but with an empty body using the Empty struct.
 macro_rules! post_arg_empty {
-    ($name:ident, $url:expr, $arg_type:ty) => {
+    ($name:ident, $url:expr, $arg_type:ty, $scopes:tt) => {
         async fn $name(
             &self,
             arg: &$arg_type,
         ) -> miette::Result<crate::PostResponse, crate::error::TeslatteError> {
+            crate::check_scope!(self, $name, $scopes)?;
             let url = format!($url, arg);
-            let url = format!("{}{}", crate::API_URL, url);
+            let url = format!("{}{}", self.api_url, url);
             self.post(&url, &Empty {}).await
         }
     };
@@ -498,4 +922,90 @@ mod tests {
             panic!("expected an error");
         }
     }
+
+    #[test]
+    fn vehicle_unavailable() {
+        let request_data = RequestData::Get {
+            url: "https://example.com/api/1/vehicles/1/vehicle_data",
+        };
+        let s = r#"{
+            "response": null,
+            "error": "vehicle unavailable: vehicle is offline or asleep",
+            "error_description": ""
+        }"#;
+        let e =
+            OwnerApi::parse_json::<ChargeState>(&request_data, s.to_string(), PrintResponses::No)
+                .unwrap_err();
+        assert!(e.is_vehicle_unavailable());
+
+        let s = r#"{"response":null,"error":"invalid bearer token","error_description":""}"#;
+        let e =
+            OwnerApi::parse_json::<ChargeState>(&request_data, s.to_string(), PrintResponses::No)
+                .unwrap_err();
+        assert!(!e.is_vehicle_unavailable());
+    }
+
+    #[test]
+    fn errors_are_redacted() {
+        let secrets = ["5YJ3E1EA1KF000000", "47.123", "8.654", "1234"];
+        let assert_redacted = |e: TeslatteError| {
+            let s = format!("{e} {e:?}");
+            for secret in secrets {
+                assert!(!s.contains(secret), "{secret} in {s}");
+            }
+        };
+
+        let request_data = RequestData::Post {
+            url: "https://example.com/api/1/vehicles/5YJ3E1EA1KF000000/command/set_valet_mode",
+            payload: r#"{"on":true,"password":"1234"}"#,
+        };
+        let s = r#"{"response":null,"error":{"error":"5YJ3E1EA1KF000000 is unavailable"},"latitude":47.123}"#;
+        let e =
+            OwnerApi::parse_json::<ChargeState>(&request_data, s.to_string(), PrintResponses::No)
+                .unwrap_err();
+        assert!(matches!(e, TeslatteError::ServerError { .. }));
+        assert_redacted(e);
+
+        let request_data = RequestData::Post {
+            url: "https://example.com/api/1/vehicles/5YJ3E1EA1KF000000/command/window_control",
+            payload: r#"{"command":"close","lat":47.123,"lon":8.654}"#,
+        };
+        let s = r#"{"response":{"latitude":47.123,"longitude":8.654}}"#;
+        let e =
+            OwnerApi::parse_json::<ChargeState>(&request_data, s.to_string(), PrintResponses::No)
+                .unwrap_err();
+        assert!(matches!(e, TeslatteError::DecodeJsonError { .. }));
+        assert_redacted(e);
+    }
+
+    #[tokio::test]
+    async fn missing_scope() {
+        let mut api = OwnerApi::new(AccessToken::new(""), None);
+        api.scopes = Some(vec![Scope::VehicleDeviceData]);
+
+        let e = api.honk_horn(&VehicleId::new(1)).await.unwrap_err();
+        if let TeslatteError::MissingScope { endpoint, scope } = e {
+            assert_eq!(endpoint, "honk_horn");
+            assert_eq!(scope, Scope::VehicleCmds);
+        } else {
+            panic!("unexpected error: {:?}", e);
+        }
+    }
+
+    #[tokio::test]
+    async fn location_data_needs_location_scope() {
+        let mut api = OwnerApi::new(AccessToken::new(""), None);
+        api.scopes = Some(vec![Scope::VehicleDeviceData]);
+
+        let get_vehicle_data =
+            GetVehicleData::new_with_endpoints(1u64, vec![vehicles::Endpoint::LocationData]);
+        let e = api.vehicle_data(&get_vehicle_data).await.unwrap_err();
+        assert!(matches!(
+            e,
+            TeslatteError::MissingScope {
+                scope: Scope::VehicleLocation,
+                ..
+            }
+        ));
+    }
 }
@@ -1,9 +1,18 @@
 use clap::{Args, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
-use teslatte::auth::{AccessToken, RefreshToken};
+use teslatte::auth::{AccessToken, AuthConfig, AuthRegion, RefreshToken};
+use teslatte::cli::charging::ChargingArgs;
 use teslatte::cli::energy::EnergySiteArgs;
+use teslatte::cli::fleet_telemetry::FleetTelemetryArgs;
 use teslatte::cli::powerwall::PowerwallArgs;
+use teslatte::cli::print_table;
+#[cfg(feature = "telemetry")]
+use teslatte::cli::telemetry::TelemetryArgs;
+use teslatte::cli::token::TokenArgs;
 use teslatte::cli::vehicle::VehicleArgs;
+use teslatte::cli::vin::VinArgs;
+use teslatte::sharing::RedeemShareInvite;
+use teslatte::vehicles::Vehicle;
 use teslatte::{OwnerApi, PrintResponses};
 
 /// Teslatte
@@ -25,6 +34,10 @@ enum Command {
         /// Be careful with your access tokens!
         #[clap(short, long)]
         save: bool,
+
+        /// `global`, or `china` for accounts in mainland China.
+        #[clap(short, long, default_value = "global")]
+        region: AuthRegion,
     },
 
     /// Refresh your tokens.
@@ -33,10 +46,22 @@ enum Command {
         /// update the file.
         #[clap(short, long, env = "TESLA_REFRESH_TOKEN")]
         refresh_token: Option<RefreshToken>,
+
+        /// `global` or `china`. If not provided, will use the region in the cli.json file, or
+        /// `global`.
+        #[clap(long, env = "TESLA_REGION")]
+        region: Option<AuthRegion>,
     },
 
+    /// Inspect your access token without calling the API.
+    Token(TokenArgs),
+
     /// Run API commands.
     Api(ApiArgs),
+
+    /// Receive Fleet Telemetry from vehicles.
+    #[cfg(feature = "telemetry")]
+    Telemetry(TelemetryArgs),
 }
 
 #[derive(Debug, Args)]
@@ -45,6 +70,11 @@ struct ApiArgs {
     #[clap(short, long, env = "TESLA_ACCESS_TOKEN")]
     access_token: Option<AccessToken>,
 
+    /// `global` or `china`. If not provided, will use the region in the cli.json file, or
+    /// `global`.
+    #[clap(long, env = "TESLA_REGION")]
+    region: Option<AuthRegion>,
+
     #[clap(subcommand)]
     command: ApiCommand,
 }
@@ -57,6 +87,9 @@ enum ApiCommand {
     /// Specific Vehicle.
     Vehicle(VehicleArgs),
 
+    /// Fleet API information about a vehicle by VIN, e.g. specs and warranty.
+    Vin(VinArgs),
+
     /// List of energy sites.
     Products,
 
@@ -65,6 +98,15 @@ enum ApiCommand {
 
     /// Powerwall queries.
     Powerwall(PowerwallArgs),
+
+    /// Charging history, business fleet charging sessions and invoices.
+    Charging(ChargingArgs),
+
+    /// Configure vehicles to stream to a Fleet Telemetry server.
+    FleetTelemetry(FleetTelemetryArgs),
+
+    /// Accept an invite to a vehicle shared with you.
+    RedeemInvite { code: String },
 }
 
 #[tokio::main]
@@ -74,43 +116,67 @@ async fn main() -> miette::Result<()> {
     let args = Cli::parse();
 
     match args.command {
-        Command::Auth { save } => {
-            let api = OwnerApi::from_interactive_url().await?;
-            print_or_save_tokens(save, &api);
+        Command::Auth { save, region } => {
+            let api = OwnerApi::from_interactive_url(&AuthConfig::new(region)).await?;
+            print_or_save_tokens(save, region, &api);
         }
-        Command::Refresh { refresh_token } => {
-            let (save, refresh_token) = match refresh_token {
-                Some(refresh_token) => (false, refresh_token),
+        Command::Refresh {
+            refresh_token,
+            region,
+        } => {
+            let (save, refresh_token, region) = match refresh_token {
+                Some(refresh_token) => (false, refresh_token, region.unwrap_or_default()),
                 None => {
                     let config = Config::load();
-                    (true, config.refresh_token)
+                    (true, config.refresh_token, region.unwrap_or(config.region))
                 }
             };
 
-            let api = OwnerApi::from_refresh_token(&refresh_token).await?;
-            print_or_save_tokens(save, &api);
+            let api =
+                OwnerApi::from_refresh_token(&AuthConfig::new(region), &refresh_token).await?;
+            print_or_save_tokens(save, region, &api);
+        }
+        Command::Token(token_args) => {
+            let access_token = match &token_args.access_token {
+                Some(a) => a.clone(),
+                None => Config::load().access_token,
+            };
+            token_args.run(&access_token)?;
+        }
+        #[cfg(feature = "telemetry")]
+        Command::Telemetry(telemetry_args) => {
+            telemetry_args.run().await?;
         }
         Command::Api(api_args) => {
-            let (access_token, refresh_token) = match &api_args.access_token {
-                Some(a) => (a.clone(), None),
+            let (access_token, refresh_token, region) = match &api_args.access_token {
+                Some(a) => (a.clone(), None, api_args.region.unwrap_or_default()),
                 None => {
                     let config = Config::load();
                     (
                         config.access_token.clone(),
                         Some(config.refresh_token.clone()),
+                        api_args.region.unwrap_or(config.region),
                     )
                 }
             };
 
-            let mut api = OwnerApi::new(access_token, refresh_token);
+            let mut api = OwnerApi::new_with_auth_config(
+                AuthConfig::new(region),
+                access_token,
+                refresh_token,
+            );
             api.print_responses = PrintResponses::Pretty;
             match api_args.command {
                 ApiCommand::Vehicles => {
-                    panic!("Tesla API has changed. This command is no longer works with the Owners API.");
+                    api.print_responses = PrintResponses::No;
+                    print_vehicles(&api.vehicles().await?);
                 }
                 ApiCommand::Vehicle(v) => {
                     v.run(&api).await?;
                 }
+                ApiCommand::Vin(v) => {
+                    v.run(&api).await?;
+                }
                 ApiCommand::Products => {
                     api.products().await?;
                 }
@@ -120,13 +186,39 @@ async fn main() -> miette::Result<()> {
                 ApiCommand::Powerwall(p) => {
                     p.run(&api).await?;
                 }
+                ApiCommand::Charging(c) => {
+                    c.run(&api).await?;
+                }
+                ApiCommand::FleetTelemetry(f) => {
+                    f.run(&api).await?;
+                }
+                ApiCommand::RedeemInvite { code } => {
+                    api.share_invites_redeem(&RedeemShareInvite { code })
+                        .await?;
+                }
             }
         }
     }
     Ok(())
 }
 
-fn print_or_save_tokens(save: bool, api: &OwnerApi) {
+fn print_vehicles(vehicles: &[Vehicle]) {
+    let rows = vehicles
+        .iter()
+        .map(|v| {
+            [
+                v.id.to_string(),
+                v.vin.clone(),
+                v.display_name.clone().unwrap_or_default(),
+                v.state.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    print_table(["ID", "VIN", "NAME", "STATE"], rows);
+}
+
+fn print_or_save_tokens(save: bool, region: AuthRegion, api: &OwnerApi) {
     let access_token = api.access_token.clone();
     let refresh_token = api.refresh_token.clone().unwrap();
 
@@ -134,12 +226,13 @@ fn print_or_save_tokens(save: bool, api: &OwnerApi) {
         Config {
             access_token,
             refresh_token,
+            region,
         }
         .save();
         println!("Saved tokens to cli.json");
     } else {
-        println!("Access token: {}", access_token);
-        println!("Refresh token: {}", refresh_token);
+        println!("Access token: {}", access_token.expose());
+        println!("Refresh token: {}", refresh_token.expose());
     }
 }
 
@@ -147,6 +240,8 @@ fn print_or_save_tokens(save: bool, api: &OwnerApi) {
 struct Config {
     access_token: AccessToken,
     refresh_token: RefreshToken,
+    #[serde(default)]
+    region: AuthRegion,
 }
 
 impl Config {
@@ -0,0 +1,407 @@
+//! Poll a vehicle without stopping it from falling asleep.
+//!
+//! Requesting vehicle data keeps a vehicle awake. [VehicleMonitor] polls often while the vehicle
+//! is driving or charging. Once it has been idle for [MonitorConfig::idle_timeout], it only
+//! checks the vehicle summary for [MonitorConfig::suspend_duration], which gives the vehicle a
+//! chance to sleep. While it is asleep, only the summary is checked, which doesn't wake it.
+//!
+//! The summary only tells whether the vehicle is online, so a drive or charge that starts while
+//! the vehicle is left to fall asleep is only noticed once [MonitorConfig::suspend_duration] is
+//! over.
+//!
+//! ```no_run
+//! # use futures_util::StreamExt;
+//! # use std::sync::Arc;
+//! # use teslatte::monitor::{MonitorConfig, VehicleMonitor};
+//! # async fn run(api: teslatte::OwnerApi, vehicle_id: teslatte::VehicleId) {
+//! let mut monitor = VehicleMonitor::spawn(Arc::new(api), vehicle_id, MonitorConfig::default());
+//! while let Some(snapshot) = monitor.next().await {
+//!     match snapshot {
+//!         Ok(snapshot) => println!("{}: {}", snapshot.time, snapshot.state),
+//!         Err(e) => eprintln!("{e}"),
+//!     }
+//! }
+//! # }
+//! ```
+use crate::error::TeslatteError;
+use crate::vehicles::{Endpoint, GetVehicleData, OnlineState, SoftwareUpdateStatus, VehicleData};
+use crate::{OwnerApi, VehicleApi, VehicleId};
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// How often [VehicleMonitor] polls.
+///
+/// While the vehicle is left to fall asleep, a drive or charge that starts isn't noticed until
+/// [MonitorConfig::suspend_duration] is over, because the summary doesn't show it. A shorter
+/// suspension notices it sooner, but gives the vehicle less of a chance to sleep.
+#[derive(Debug, Clone)]
+pub struct MonitorConfig {
+    /// Endpoints to request while the vehicle is awake.
+    pub endpoints: Vec<Endpoint>,
+    pub driving_interval: Duration,
+    pub charging_interval: Duration,
+    /// While a software update is installing.
+    pub updating_interval: Duration,
+    /// While the vehicle is awake, but not driving or charging.
+    pub online_interval: Duration,
+    /// While the vehicle is asleep or offline, or being left to fall asleep.
+    pub asleep_interval: Duration,
+    /// After an error, other than the vehicle not being online.
+    pub error_interval: Duration,
+    /// How long the vehicle has to be idle before it is left to fall asleep.
+    pub idle_timeout: Duration,
+    /// How long vehicle data isn't requested for, once the vehicle is idle. It ends early when
+    /// the vehicle falls asleep or goes offline, but not when it starts driving or charging.
+    pub suspend_duration: Duration,
+    /// Snapshots to keep when they aren't being read, before polling waits.
+    pub buffer: usize,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: vec![
+                Endpoint::ChargeState,
+                Endpoint::ClimateState,
+                Endpoint::DriveState,
+                Endpoint::LocationData,
+                Endpoint::VehicleState,
+            ],
+            driving_interval: Duration::from_secs(5),
+            charging_interval: Duration::from_secs(15),
+            updating_interval: Duration::from_secs(30),
+            online_interval: Duration::from_secs(15),
+            asleep_interval: Duration::from_secs(30),
+            error_interval: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(15 * 60),
+            suspend_duration: Duration::from_secs(21 * 60),
+            buffer: 16,
+        }
+    }
+}
+
+/// The vehicle at one point in time.
+#[derive(Debug, Clone)]
+pub struct VehicleSnapshot {
+    pub time: DateTime<Utc>,
+    pub state: OnlineState,
+    /// Not requested while the vehicle is asleep or offline, or being left to fall asleep.
+    pub data: Option<VehicleData>,
+}
+
+/// A [Stream] of a [VehicleSnapshot] each time the vehicle is polled. Errors don't end the
+/// stream. Polling stops when this is dropped.
+#[derive(Debug)]
+pub struct VehicleMonitor {
+    snapshots: mpsc::Receiver<Result<VehicleSnapshot, TeslatteError>>,
+    task: JoinHandle<()>,
+}
+
+impl VehicleMonitor {
+    pub fn spawn(api: Arc<OwnerApi>, vehicle_id: VehicleId, config: MonitorConfig) -> Self {
+        let (tx, snapshots) = mpsc::channel(config.buffer);
+        let task = tokio::spawn(poll(api, vehicle_id, config, tx));
+        Self { snapshots, task }
+    }
+
+    /// The next snapshot, without needing [futures_util::StreamExt].
+    pub async fn recv(&mut self) -> Option<Result<VehicleSnapshot, TeslatteError>> {
+        self.snapshots.recv().await
+    }
+}
+
+impl Stream for VehicleMonitor {
+    type Item = Result<VehicleSnapshot, TeslatteError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.snapshots.poll_recv(cx)
+    }
+}
+
+impl Drop for VehicleMonitor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn poll(
+    api: Arc<OwnerApi>,
+    vehicle_id: VehicleId,
+    config: MonitorConfig,
+    tx: mpsc::Sender<Result<VehicleSnapshot, TeslatteError>>,
+) {
+    let mut get_vehicle_data =
+        GetVehicleData::new_with_endpoints(vehicle_id.clone(), config.endpoints.clone());
+    let mut schedule = Schedule::new(config);
+
+    loop {
+        let now = Instant::now();
+        let (snapshot, interval) = if schedule.is_suspended(now) {
+            match api.vehicle(&vehicle_id).await {
+                Ok(vehicle) => (
+                    snapshot(vehicle.state, None),
+                    schedule.on_summary(vehicle.state),
+                ),
+                Err(e) => (Err(e), schedule.config.error_interval),
+            }
+        } else {
+            // Checking the summary first is a second request, so skip it while the vehicle is
+            // known to be online.
+            get_vehicle_data.require_online = !schedule.online;
+            let mut result = api.vehicle_data(&get_vehicle_data).await;
+            let unavailable = matches!(&result, Err(e) if e.is_vehicle_unavailable());
+            if unavailable && !get_vehicle_data.require_online {
+                // It may have fallen asleep since, which the summary tells. Other errors, e.g. a
+                // missing scope, wouldn't go away by asking again.
+                get_vehicle_data.require_online = true;
+                result = api.vehicle_data(&get_vehicle_data).await;
+            }
+            match result {
+                Ok(data) => {
+                    let interval = schedule.on_data(now, &data);
+                    (snapshot(OnlineState::Online, Some(data)), interval)
+                }
+                Err(TeslatteError::VehicleNotOnline { state, .. }) => {
+                    (snapshot(state, None), schedule.on_summary(state))
+                }
+                Err(e) => (Err(e), schedule.on_error()),
+            }
+        };
+
+        if tx.send(snapshot).await.is_err() {
+            return;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn snapshot(
+    state: OnlineState,
+    data: Option<VehicleData>,
+) -> Result<VehicleSnapshot, TeslatteError> {
+    Ok(VehicleSnapshot {
+        time: Utc::now(),
+        state,
+        data,
+    })
+}
+
+/// What the vehicle is doing, as far as polling is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Activity {
+    Driving,
+    Charging,
+    Updating,
+    /// Awake for a reason, e.g. someone is in it or sentry mode is on.
+    Busy,
+    Idle,
+}
+
+impl Activity {
+    fn of(data: &VehicleData) -> Self {
+        if data.is_driving() {
+            return Activity::Driving;
+        }
+        if data.is_charging() {
+            return Activity::Charging;
+        }
+        if data.is_updating() {
+            return Activity::Updating;
+        }
+
+        let update_status = data
+            .vehicle_state
+            .as_ref()
+            .and_then(|v| v.software_update.status());
+        if update_status == Some(SoftwareUpdateStatus::Downloading) {
+            return Activity::Busy;
+        }
+
+        let user_present_or_sentry = data
+            .vehicle_state
+            .as_ref()
+            .is_some_and(|v| v.is_user_present || v.sentry_mode == Some(true));
+        let climate_on = data
+            .climate_state
+            .as_ref()
+            .is_some_and(|c| c.is_climate_on || c.is_preconditioning);
+        if user_present_or_sentry || climate_on {
+            return Activity::Busy;
+        }
+
+        Activity::Idle
+    }
+}
+
+/// When to poll next, and whether to leave the vehicle to fall asleep.
+#[derive(Debug)]
+struct Schedule {
+    config: MonitorConfig,
+    idle_since: Option<Instant>,
+    suspended_until: Option<Instant>,
+    /// The vehicle was online when it was last polled.
+    online: bool,
+}
+
+impl Schedule {
+    fn new(config: MonitorConfig) -> Self {
+        Self {
+            config,
+            idle_since: None,
+            suspended_until: None,
+            online: false,
+        }
+    }
+
+    fn is_suspended(&self, now: Instant) -> bool {
+        self.suspended_until.is_some_and(|until| now < until)
+    }
+
+    fn on_data(&mut self, now: Instant, data: &VehicleData) -> Duration {
+        self.online = true;
+        self.suspended_until = None;
+        let activity = Activity::of(data);
+        if activity != Activity::Idle {
+            self.idle_since = None;
+        }
+
+        match activity {
+            Activity::Driving => self.config.driving_interval,
+            Activity::Charging => self.config.charging_interval,
+            Activity::Updating => self.config.updating_interval,
+            Activity::Busy => self.config.online_interval,
+            Activity::Idle => {
+                let idle_since = *self.idle_since.get_or_insert(now);
+                if now.duration_since(idle_since) >= self.config.idle_timeout {
+                    self.idle_since = None;
+                    self.suspended_until = Some(now + self.config.suspend_duration);
+                    self.config.asleep_interval
+                } else {
+                    self.config.online_interval
+                }
+            }
+        }
+    }
+
+    /// Only the summary was checked, because the vehicle isn't online or is being left to fall
+    /// asleep.
+    fn on_summary(&mut self, state: OnlineState) -> Duration {
+        self.online = state == OnlineState::Online;
+        if state != OnlineState::Online {
+            // It fell asleep, so there is no need to keep waiting.
+            self.idle_since = None;
+            self.suspended_until = None;
+        }
+        self.config.asleep_interval
+    }
+
+    /// The request failed, so check the summary again next time.
+    fn on_error(&mut self) -> Duration {
+        self.online = false;
+        self.config.error_interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PrintResponses, RequestData};
+
+    fn vehicle_data() -> VehicleData {
+        let s = include_str!("../testdata/vehicle_data_HTLC_2023_10_19.json");
+        let request_data = RequestData::Get { url: "" };
+        OwnerApi::parse_json::<VehicleData>(&request_data, s.to_string(), PrintResponses::No)
+            .unwrap()
+    }
+
+    #[test]
+    fn activity() {
+        let mut data = vehicle_data();
+        assert_eq!(Activity::of(&data), Activity::Idle);
+
+        let sentry = include_str!("../testdata/vehicle_data_gak_2023_11_11.json");
+        let sentry = OwnerApi::parse_json::<VehicleData>(
+            &RequestData::Get { url: "" },
+            sentry.to_string(),
+            PrintResponses::No,
+        )
+        .unwrap();
+        assert_eq!(Activity::of(&sentry), Activity::Busy);
+
+        data.charge_state.as_mut().unwrap().charging_state = "Charging".to_string();
+        assert_eq!(Activity::of(&data), Activity::Charging);
+
+        data.drive_state.as_mut().unwrap().shift_state = Some("D".to_string());
+        assert_eq!(Activity::of(&data), Activity::Driving);
+    }
+
+    #[test]
+    fn suspends_when_idle() {
+        let config = MonitorConfig::default();
+        let mut schedule = Schedule::new(config.clone());
+        let idle = vehicle_data();
+        let start = Instant::now();
+
+        assert_eq!(schedule.on_data(start, &idle), config.online_interval);
+        let later = start + config.idle_timeout - Duration::from_secs(1);
+        assert_eq!(schedule.on_data(later, &idle), config.online_interval);
+        assert!(!schedule.is_suspended(later));
+
+        let idle_timeout = start + config.idle_timeout;
+        assert_eq!(
+            schedule.on_data(idle_timeout, &idle),
+            config.asleep_interval
+        );
+        assert!(schedule.is_suspended(idle_timeout));
+        assert_eq!(
+            schedule.on_summary(OnlineState::Online),
+            config.asleep_interval
+        );
+        assert!(schedule.is_suspended(idle_timeout + config.suspend_duration / 2));
+        assert!(!schedule.is_suspended(idle_timeout + config.suspend_duration));
+
+        // Falling asleep ends the suspension.
+        schedule.on_summary(OnlineState::Asleep);
+        assert!(!schedule.is_suspended(idle_timeout));
+    }
+
+    #[test]
+    fn remembers_online() {
+        let config = MonitorConfig::default();
+        let mut schedule = Schedule::new(config.clone());
+        assert!(!schedule.online);
+
+        schedule.on_data(Instant::now(), &vehicle_data());
+        assert!(schedule.online);
+        assert_eq!(schedule.on_error(), config.error_interval);
+        assert!(!schedule.online);
+        schedule.on_summary(OnlineState::Online);
+        assert!(schedule.online);
+        schedule.on_summary(OnlineState::Asleep);
+        assert!(!schedule.online);
+    }
+
+    #[test]
+    fn driving_resets_idle() {
+        let config = MonitorConfig::default();
+        let mut schedule = Schedule::new(config.clone());
+        let idle = vehicle_data();
+        let mut driving = vehicle_data();
+        driving.drive_state.as_mut().unwrap().shift_state = Some("D".to_string());
+        let start = Instant::now();
+
+        schedule.on_data(start, &idle);
+        let later = start + config.idle_timeout / 2;
+        assert_eq!(schedule.on_data(later, &driving), config.driving_interval);
+        // Idle time counts again from when the drive ended.
+        let after_drive = start + config.idle_timeout;
+        assert_eq!(schedule.on_data(after_drive, &idle), config.online_interval);
+        assert!(!schedule.is_suspended(after_drive));
+    }
+}
@@ -7,8 +7,8 @@ use serde::{Deserialize, Serialize};
 
 #[rustfmt::skip]
 impl OwnerApi {
-    pub_get_arg!(powerwall_status, PowerwallStatus, "/powerwalls/{}/status", PowerwallId);
-    pub_get_args!(powerwall_energy_history, PowerwallEnergyHistory, "/powerwalls/{}/energyhistory", PowerwallEnergyHistoryValues);
+    pub_get_arg!(powerwall_status, PowerwallStatus, "/powerwalls/{}/status", PowerwallId, [EnergyDeviceData]);
+    pub_get_args!(powerwall_energy_history, PowerwallEnergyHistory, "/powerwalls/{}/energyhistory", PowerwallEnergyHistoryValues, [EnergyDeviceData]);
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Display, FromStr)]
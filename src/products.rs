@@ -10,7 +10,7 @@ use std::str::FromStr;
 
 #[rustfmt::skip]
 impl OwnerApi {
-    pub_get!(products, Vec<Product>, "/products");
+    pub_get!(products, Vec<Product>, "/products", []);
 }
 
 #[derive(Debug, Clone, Deserialize, Display)]
@@ -0,0 +1,176 @@
+//! Redaction of sensitive values before they are logged.
+//!
+//! Responses from the API contain tokens, VINs and the location of the vehicle. [OwnerApi] logs
+//! requests and responses at the debug level, so they are passed through [redact()] first.
+//!
+//! [OwnerApi]: crate::OwnerApi
+
+use serde_json::Value;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Implement `Debug` and `Display` for a secret newtype so that they never show the value.
+macro_rules! redacted_fmt {
+    ($type:ty) => {
+        impl std::fmt::Debug for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple(stringify!($type))
+                    .field(&crate::redact::REDACTED)
+                    .finish()
+            }
+        }
+
+        impl std::fmt::Display for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(crate::redact::REDACTED)
+            }
+        }
+    };
+}
+pub(crate) use redacted_fmt;
+
+/// JSON keys whose values are replaced with [REDACTED], wherever they appear.
+const SENSITIVE_KEYS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "token",
+    "tokens",
+    "backseat_token",
+    "password",
+    "pin",
+    "share_link",
+    "vin",
+    "latitude",
+    "longitude",
+    "native_latitude",
+    "native_longitude",
+    "corrected_latitude",
+    "corrected_longitude",
+    "active_route_latitude",
+    "active_route_longitude",
+    "lat",
+    "lon",
+    "long",
+];
+
+/// Redact a request or response body for logging.
+///
+/// JSON has the values of sensitive keys replaced. Anything else, or anything left over in JSON
+/// strings, has VINs replaced.
+pub fn redact(s: &str) -> String {
+    match serde_json::from_str::<Value>(s) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => redact_vins(s),
+    }
+}
+
+/// JSON keys whose values are replaced with [REDACTED] in share invites only. Elsewhere `code` is
+/// e.g. the code of an API error.
+const SHARE_INVITE_KEYS: &[&str] = &["code"];
+
+/// A share invite has a `share_link`, and the body of `share_invites_redeem` is only the `code`.
+fn is_share_invite(map: &serde_json::Map<String, Value>) -> bool {
+    map.contains_key("share_link") || map.keys().eq(["code"])
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let share_invite = is_share_invite(map);
+            for (key, value) in map.iter_mut() {
+                if SENSITIVE_KEYS.contains(&key.as_str())
+                    || (share_invite && SHARE_INVITE_KEYS.contains(&key.as_str()))
+                {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact_value),
+        Value::String(s) => *s = redact_vins(s),
+        _ => {}
+    }
+}
+
+/// Replace anything that looks like a VIN: 17 characters of digits and capital letters except
+/// I, O and Q.
+pub fn redact_vins(s: &str) -> String {
+    const VIN_LEN: usize = 17;
+
+    let chars = s.chars().collect::<Vec<_>>();
+    let mut out = String::with_capacity(s.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let run = chars[i..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .count();
+        if run == 0 {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        let word = &chars[i..i + run];
+        if run == VIN_LEN && is_vin(word) {
+            out.push_str(REDACTED);
+        } else {
+            out.extend(word);
+        }
+        i += run;
+    }
+    out
+}
+
+fn is_vin(word: &[char]) -> bool {
+    word.iter()
+        .all(|c| c.is_ascii_digit() || (c.is_ascii_uppercase() && !matches!(c, 'I' | 'O' | 'Q')))
+        && word.iter().any(|c| c.is_ascii_digit())
+        && word.iter().any(|c| c.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json() {
+        let s = r#"{"response":{"vin":"5YJ3E1EA1KF000000","tokens":["abc"],"drive_state":{"latitude":47.0,"longitude":8.6,"speed":null},"display_name":"HTLC"}}"#;
+        assert_eq!(
+            redact(s),
+            r#"{"response":{"display_name":"HTLC","drive_state":{"latitude":"[REDACTED]","longitude":"[REDACTED]","speed":null},"tokens":"[REDACTED]","vin":"[REDACTED]"}}"#
+        );
+    }
+
+    #[test]
+    fn share_invite_code() {
+        assert_eq!(
+            redact(
+                r#"{"response":{"code":"aqwl4JHU","share_link":"https://www.tesla.com/_rs/1/aqwl4JHU"}}"#
+            ),
+            r#"{"response":{"code":"[REDACTED]","share_link":"[REDACTED]"}}"#
+        );
+        assert_eq!(redact(r#"{"code":"aqwl4JHU"}"#), r#"{"code":"[REDACTED]"}"#);
+        assert_eq!(
+            redact(r#"{"error":"invalid_request","code":"not_found"}"#),
+            r#"{"code":"not_found","error":"invalid_request"}"#
+        );
+    }
+
+    #[test]
+    fn vin_in_text() {
+        assert_eq!(
+            redact("GET https://example.com/api/1/vehicles/5YJ3E1EA1KF000000/specs"),
+            "GET https://example.com/api/1/vehicles/[REDACTED]/specs"
+        );
+        assert_eq!(
+            redact_vins("1234567890123456 HELLOWORLDHELLOWORLD"),
+            "1234567890123456 HELLOWORLDHELLOWORLD"
+        );
+    }
+}
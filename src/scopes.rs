@@ -0,0 +1,296 @@
+//! OAuth scopes required by each endpoint.
+//!
+//! Fleet API tokens carry the scopes the user granted in the `scp` claim. A call to an endpoint
+//! without the right scope fails with a 403 that doesn't say why, so [OwnerApi] checks the scopes
+//! before sending the request when it knows the scopes of its token.
+//!
+//! Each endpoint names its scopes where it is defined, e.g.
+//! `post_arg_empty!(honk_horn, "/vehicles/{}/command/honk_horn", VehicleId, [VehicleCmds])`.
+//! Those have to match [ENDPOINT_SCOPES], or the crate doesn't compile, so the table can be used
+//! to see which endpoints a grant unlocks.
+//!
+//! Owner API tokens don't use these scopes, so no checks are done for them.
+//!
+//! [OwnerApi]: crate::OwnerApi
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString, IntoStaticStr};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumString,
+    IntoStaticStr,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Scope {
+    Openid,
+    OfflineAccess,
+    UserData,
+    VehicleDeviceData,
+    VehicleLocation,
+    VehicleCmds,
+    VehicleChargingCmds,
+    EnergyDeviceData,
+    /// No endpoint in this crate sends energy commands yet.
+    EnergyCmds,
+}
+
+use Scope::*;
+
+const CHARGING: &[Scope] = &[VehicleChargingCmds, VehicleCmds];
+
+/// Endpoint function name, and the scopes that allow calling it. Any one of the scopes is enough,
+/// and an empty list means any scope will do.
+///
+/// `vehicle_data` also needs [VehicleLocation] when
+/// [Endpoint::LocationData](crate::vehicles::Endpoint::LocationData) is requested.
+#[rustfmt::skip]
+pub const ENDPOINT_SCOPES: &[(&str, &[Scope])] = &[
+    ("products", &[]),
+    ("vehicle", &[VehicleDeviceData]),
+    ("vehicle_data", &[VehicleDeviceData]),
+    ("stream_vehicle", &[VehicleDeviceData]),
+    ("nearby_charging_sites", &[VehicleDeviceData]),
+    ("wake_up", &[VehicleDeviceData]),
+
+    // Alerts
+    ("honk_horn", &[VehicleCmds]),
+    ("flash_lights", &[VehicleCmds]),
+
+    // Charging
+    ("charge_port_door_open", CHARGING),
+    ("charge_port_door_close", CHARGING),
+    ("set_charge_limit", CHARGING),
+    ("set_charging_amps", CHARGING),
+    ("charge_standard", CHARGING),
+    ("charge_max_range", CHARGING),
+    ("charge_start", CHARGING),
+    ("charge_stop", CHARGING),
+    ("set_scheduled_charging", CHARGING),
+    ("set_scheduled_departure", CHARGING),
+    ("add_charge_schedule", CHARGING),
+    ("remove_charge_schedule", CHARGING),
+    ("add_precondition_schedule", &[VehicleCmds]),
+    ("remove_precondition_schedule", &[VehicleCmds]),
+
+    // HVAC
+    ("auto_conditioning_start", &[VehicleCmds]),
+    ("auto_conditioning_stop", &[VehicleCmds]),
+    ("set_temps", &[VehicleCmds]),
+    ("remote_seat_heater_request", &[VehicleCmds]),
+    ("remote_seat_cooler_request", &[VehicleCmds]),
+    ("remote_auto_seat_climate_request", &[VehicleCmds]),
+    ("remote_steering_wheel_heater_request", &[VehicleCmds]),
+    ("remote_steering_wheel_heat_level_request", &[VehicleCmds]),
+    ("remote_auto_steering_wheel_heat_climate_request", &[VehicleCmds]),
+    ("set_climate_keeper_mode", &[VehicleCmds]),
+    ("set_cabin_overheat_protection", &[VehicleCmds]),
+    ("set_cop_temp", &[VehicleCmds]),
+    ("set_bioweapon_mode", &[VehicleCmds]),
+    ("set_preconditioning_max", &[VehicleCmds]),
+
+    // Doors
+    ("door_unlock", &[VehicleCmds]),
+    ("door_lock", &[VehicleCmds]),
+    ("remote_start_drive", &[VehicleCmds]),
+    ("actuate_trunk", &[VehicleCmds]),
+
+    // Windows
+    ("window_control", &[VehicleCmds]),
+    ("sun_roof_control", &[VehicleCmds]),
+
+    // Security
+    ("set_sentry_mode", &[VehicleCmds]),
+    ("set_valet_mode", &[VehicleCmds]),
+    ("reset_valet_pin", &[VehicleCmds]),
+    ("set_pin_to_drive", &[VehicleCmds]),
+    ("reset_pin_to_drive_pin", &[VehicleCmds]),
+
+    // Speed limit
+    ("speed_limit_activate", &[VehicleCmds]),
+    ("speed_limit_deactivate", &[VehicleCmds]),
+    ("speed_limit_set_limit", &[VehicleCmds]),
+    ("speed_limit_clear_pin", &[VehicleCmds]),
+
+    // Media
+    ("adjust_volume", &[VehicleCmds]),
+    ("media_toggle_playback", &[VehicleCmds]),
+    ("media_next_track", &[VehicleCmds]),
+    ("media_prev_track", &[VehicleCmds]),
+    ("media_next_fav", &[VehicleCmds]),
+    ("media_prev_fav", &[VehicleCmds]),
+    ("media_volume_up", &[VehicleCmds]),
+    ("media_volume_down", &[VehicleCmds]),
+
+    // Navigation
+    ("navigation_request", &[VehicleCmds]),
+    ("navigation_gps_request", &[VehicleCmds]),
+    ("navigation_sc_request", &[VehicleCmds]),
+    ("navigation_waypoints_request", &[VehicleCmds]),
+
+    // Software update
+    ("schedule_software_update", &[VehicleCmds]),
+    ("cancel_software_update", &[VehicleCmds]),
+
+    // Misc
+    ("trigger_homelink", &[VehicleCmds]),
+    ("remote_boombox", &[VehicleCmds]),
+    ("set_vehicle_name", &[VehicleCmds]),
+    ("erase_user_data", &[VehicleCmds]),
+
+    // Vehicle info
+    ("vehicle_specs", &[VehicleDeviceData]),
+    ("vehicle_options", &[VehicleDeviceData]),
+    ("eligible_upgrades", &[VehicleDeviceData]),
+    ("eligible_subscriptions", &[VehicleDeviceData]),
+    ("warranty_details", &[VehicleDeviceData]),
+    ("fleet_status", &[VehicleDeviceData]),
+    ("mobile_enabled", &[VehicleDeviceData]),
+    ("recent_alerts", &[VehicleDeviceData]),
+
+    // Fleet Telemetry
+    ("fleet_telemetry_config", &[VehicleDeviceData]),
+    ("fleet_telemetry_config_create", &[VehicleDeviceData]),
+    ("fleet_telemetry_config_delete", &[VehicleDeviceData]),
+    ("fleet_telemetry_errors", &[VehicleDeviceData]),
+
+    // Sharing
+    ("share_invites", &[VehicleCmds]),
+    ("share_invites_create", &[VehicleCmds]),
+    ("share_invites_revoke", &[VehicleCmds]),
+    ("share_invites_redeem", &[VehicleCmds]),
+    ("drivers", &[VehicleDeviceData]),
+    ("drivers_remove", &[VehicleCmds]),
+
+    // Charging history
+    ("charging_history", &[VehicleChargingCmds]),
+    ("charging_sessions", &[VehicleChargingCmds]),
+    ("charging_invoice", &[VehicleChargingCmds]),
+
+    // Energy
+    ("energy_sites_site_status", &[EnergyDeviceData]),
+    ("energy_sites_live_status", &[EnergyDeviceData]),
+    ("energy_sites_site_info", &[EnergyDeviceData]),
+    ("energy_sites_calendar_history", &[EnergyDeviceData]),
+    ("powerwall_status", &[EnergyDeviceData]),
+    ("powerwall_energy_history", &[EnergyDeviceData]),
+];
+
+/// The scopes that allow calling an endpoint, or an empty slice if any scope will do.
+pub fn required_scopes(endpoint: &str) -> &'static [Scope] {
+    ENDPOINT_SCOPES
+        .iter()
+        .find(|(name, _)| *name == endpoint)
+        .map(|(_, scopes)| *scopes)
+        .unwrap_or_default()
+}
+
+/// Returns the scope that is missing to call `endpoint`, if any.
+pub fn missing_scope(endpoint: &str, granted: &[Scope]) -> Option<Scope> {
+    missing_from(required_scopes(endpoint), granted)
+}
+
+/// Returns the first of the `required` scopes when none of them were granted.
+pub(crate) fn missing_from(required: &[Scope], granted: &[Scope]) -> Option<Scope> {
+    if required.is_empty() || required.iter().any(|s| granted.contains(s)) {
+        None
+    } else {
+        Some(required[0])
+    }
+}
+
+/// Fails to compile when an endpoint isn't in [ENDPOINT_SCOPES], or is listed with other scopes.
+/// Used by the endpoint macros.
+pub(crate) const fn assert_endpoint_scopes(endpoint: &str, scopes: &[Scope]) {
+    let mut i = 0;
+    while i < ENDPOINT_SCOPES.len() {
+        let (name, listed) = ENDPOINT_SCOPES[i];
+        if bytes_eq(name.as_bytes(), endpoint.as_bytes()) {
+            if !scopes_eq(listed, scopes) {
+                panic!("endpoint scopes don't match ENDPOINT_SCOPES");
+            }
+            return;
+        }
+        i += 1;
+    }
+    panic!("endpoint is missing from ENDPOINT_SCOPES");
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn scopes_eq(a: &[Scope], b: &[Scope]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] as u8 != b[i] as u8 {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Endpoints that can be called with the granted scopes, e.g. to show which features a user's
+/// grant unlocks.
+pub fn unlocked_endpoints(granted: &[Scope]) -> Vec<&'static str> {
+    ENDPOINT_SCOPES
+        .iter()
+        .filter(|(name, _)| missing_scope(name, granted).is_none())
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing() {
+        assert_eq!(
+            missing_scope("honk_horn", &[VehicleDeviceData]),
+            Some(VehicleCmds)
+        );
+        assert_eq!(missing_scope("honk_horn", &[VehicleCmds]), None);
+        assert_eq!(missing_scope("products", &[]), None);
+    }
+
+    #[test]
+    fn charging_accepts_either_scope() {
+        assert_eq!(missing_scope("charge_start", &[VehicleCmds]), None);
+        assert_eq!(missing_scope("charge_start", &[VehicleChargingCmds]), None);
+        assert_eq!(
+            missing_scope("charge_start", &[VehicleDeviceData]),
+            Some(VehicleChargingCmds)
+        );
+    }
+
+    #[test]
+    fn unlocked() {
+        let endpoints = unlocked_endpoints(&[EnergyDeviceData]);
+        assert!(endpoints.contains(&"energy_sites_live_status"));
+        assert!(!endpoints.contains(&"vehicle_data"));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::error::TeslatteError;
+use crate::vehicles::GranularAccess;
+use crate::{check_scope, pub_get_arg, Empty, OwnerApi, VehicleId};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[rustfmt::skip]
+impl OwnerApi {
+    pub_get_arg!(share_invites, Vec<ShareInvite>, "/vehicles/{}/invitations", VehicleId, [VehicleCmds]);
+    pub_get_arg!(drivers, Vec<Driver>, "/vehicles/{}/drivers", VehicleId, [VehicleDeviceData]);
+}
+
+impl OwnerApi {
+    /// Create an invite to share the vehicle. The invite is accepted by opening
+    /// [ShareInvite::share_link], or with [OwnerApi::share_invites_redeem()].
+    pub async fn share_invites_create(
+        &self,
+        vehicle_id: &VehicleId,
+    ) -> Result<ShareInvite, TeslatteError> {
+        check_scope!(self, share_invites_create, [VehicleCmds])?;
+        let url = format!("{}/vehicles/{}/invitations", self.api_url, vehicle_id);
+        self.post_data(&url, &Empty {}).await
+    }
+
+    /// Revoke an invite, whether or not it was redeemed.
+    pub async fn share_invites_revoke(
+        &self,
+        vehicle_id: &VehicleId,
+        invite_id: i64,
+    ) -> Result<bool, TeslatteError> {
+        check_scope!(self, share_invites_revoke, [VehicleCmds])?;
+        let url = format!(
+            "{}/vehicles/{}/invitations/{}/revoke",
+            self.api_url, vehicle_id, invite_id
+        );
+        self.post_data(&url, &Empty {}).await
+    }
+
+    /// Accept an invite to a vehicle shared by someone else, using [ShareInvite::code].
+    pub async fn share_invites_redeem(
+        &self,
+        redeem: &RedeemShareInvite,
+    ) -> Result<RedeemedShareInvite, TeslatteError> {
+        check_scope!(self, share_invites_redeem, [VehicleCmds])?;
+        let url = format!("{}/invitations/redeem", self.api_url);
+        self.post_data(&url, redeem).await
+    }
+
+    /// Remove a driver from the vehicle, using [Driver::user_id].
+    ///
+    /// This is `remove_vehicle_share_driver` in the Owner API.
+    pub async fn drivers_remove(
+        &self,
+        vehicle_id: &VehicleId,
+        share_user_id: i64,
+    ) -> Result<String, TeslatteError> {
+        check_scope!(self, drivers_remove, [VehicleCmds])?;
+        let url = format!(
+            "{}/vehicles/{}/drivers?share_user_id={}",
+            self.api_url, vehicle_id, share_user_id
+        );
+        self.delete(&url).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareInvite {
+    pub id: i64,
+    pub owner_id: Option<i64>,
+    /// Set once the invite is redeemed.
+    pub share_user_id: Option<i64>,
+    pub product_id: Option<String>,
+    /// `pending`, `redeemed` or `revoked`.
+    pub state: String,
+    pub code: String,
+    pub expires_at: Option<DateTime<FixedOffset>>,
+    pub revoked_at: Option<DateTime<FixedOffset>>,
+    pub borrowing_device_id: Option<i64>,
+    pub key_id: Option<i64>,
+    pub product_type: Option<String>,
+    /// e.g. `customer`.
+    pub share_type: Option<String>,
+    pub share_user_sso_id: Option<String>,
+    pub id_s: Option<String>,
+    pub owner_id_s: Option<String>,
+    pub share_user_id_s: Option<String>,
+    pub vin: Option<String>,
+    pub share_link: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RedeemShareInvite {
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedeemedShareInvite {
+    pub vehicle_id_s: String,
+    pub vin: String,
+}
+
+/// Someone the vehicle is shared with.
+///
+/// This is `fetch_vehicle_shared_drivers` in the Owner API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Driver {
+    pub my_tesla_unique_id: Option<i64>,
+    /// Pass to [OwnerApi::drivers_remove()] as the `share_user_id`.
+    pub user_id: Option<i64>,
+    pub user_id_s: Option<String>,
+    pub vault_uuid: Option<String>,
+    pub driver_first_name: Option<String>,
+    pub driver_last_name: Option<String>,
+    pub granular_access: Option<GranularAccess>,
+    /// Can contain `null`.
+    #[serde(default)]
+    pub active_pubkeys: Vec<Option<String>>,
+    pub public_key: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PrintResponses, RequestData};
+
+    #[test]
+    fn json_share_invites() {
+        let s = r#"{
+          "response": [
+            {
+              "id": 429509621657,
+              "owner_id": 429511308124,
+              "share_user_id": null,
+              "product_id": "TEST00000000VIN01",
+              "state": "pending",
+              "code": "aqwl4JHU2q4aTeNROz8W9SpngoFvj-ReuDFIJs6-YOhA",
+              "expires_at": "2023-06-29T00:42:00.000Z",
+              "revoked_at": null,
+              "borrowing_device_id": null,
+              "key_id": null,
+              "product_type": "vehicle",
+              "share_type": "customer",
+              "share_user_sso_id": null,
+              "active_pubkeys": [null],
+              "id_s": "429509621657",
+              "owner_id_s": "429511308124",
+              "share_user_id_s": "",
+              "borrowing_key_hash": null,
+              "vin": "TEST00000000VIN01",
+              "share_link": "https://www.tesla.com/_rs/1/aqwl4JHU2q4aTeNROz8W9SpngoFvj-ReuDFIJs6-YOhA"
+            }
+          ],
+          "pagination": {
+            "previous": null,
+            "next": null,
+            "current": 1,
+            "per_page": 25,
+            "count": 1,
+            "pages": 1
+          },
+          "count": 1
+        }"#;
+
+        let request_data = RequestData::Get { url: "" };
+        let invites = OwnerApi::parse_json::<Vec<ShareInvite>>(
+            &request_data,
+            s.to_string(),
+            PrintResponses::No,
+        )
+        .unwrap();
+        assert_eq!(invites[0].id, 429509621657);
+        assert_eq!(invites[0].state, "pending");
+        assert!(invites[0].share_user_id.is_none());
+    }
+
+    #[test]
+    fn json_drivers() {
+        let s = r#"{
+          "response": [
+            {
+              "my_tesla_unique_id": 8888888,
+              "user_id": 800001,
+              "user_id_s": "800001",
+              "vault_uuid": "b5c443af-a286-49eb-a4ad-35a97963155d",
+              "driver_first_name": "Testy",
+              "driver_last_name": "McTesterson",
+              "granular_access": {
+                "hide_private": false
+              },
+              "active_pubkeys": [],
+              "public_key": ""
+            },
+            {
+              "my_tesla_unique_id": 8888889,
+              "user_id": 800002,
+              "user_id_s": "800002",
+              "vault_uuid": "c6d554b0-b397-4a5c-b5be-46ba8a74266e",
+              "driver_first_name": "Testa",
+              "driver_last_name": "McTesterson",
+              "granular_access": {
+                "hide_private": true
+              },
+              "active_pubkeys": [null],
+              "public_key": null
+            }
+          ],
+          "count": 2
+        }"#;
+
+        let request_data = RequestData::Get { url: "" };
+        let drivers =
+            OwnerApi::parse_json::<Vec<Driver>>(&request_data, s.to_string(), PrintResponses::No)
+                .unwrap();
+        assert_eq!(drivers[0].driver_first_name.as_deref(), Some("Testy"));
+        assert!(!drivers[0].granular_access.as_ref().unwrap().hide_private);
+        assert_eq!(drivers[1].active_pubkeys, vec![None]);
+    }
+}
@@ -0,0 +1,490 @@
+//! Live drive data from the Owner API streaming service. Needs the `streaming` feature.
+//!
+//! The vehicle pushes an update about every second while it is awake, which is much more often
+//! than polling [VehicleApi::vehicle_data()](crate::VehicleApi::vehicle_data) can.
+//!
+//! ```no_run
+//! # use futures_util::StreamExt;
+//! # use teslatte::streaming::StreamEvent;
+//! # async fn run(api: teslatte::OwnerApi, vehicle_id: teslatte::VehicleId) -> Result<(), teslatte::error::TeslatteError> {
+//! let mut stream = api.stream_vehicle(&vehicle_id).await?;
+//! while let Some(event) = stream.next().await {
+//!     if let StreamEvent::Data(data) = event {
+//!         println!("{:?} mph, {:?}%", data.speed, data.soc);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use crate::error::TeslatteError;
+use crate::{check_scope, OwnerApi, VehicleApi, VehicleId};
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls::{self, ClientConfig, RootCertStore};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::Connector;
+use tracing::{debug, warn};
+
+/// Columns of [StreamData], in the order the service sends them after the timestamp.
+const COLUMNS: &str =
+    "speed,odometer,soc,elevation,est_heading,est_lat,est_lng,power,shift_state,range,est_range,heading";
+
+impl OwnerApi {
+    /// Stream live data from a vehicle, reconnecting whenever the connection is lost.
+    ///
+    /// This doesn't wake the vehicle. While it is asleep, the stream reconnects every
+    /// [StreamingConfig::reconnect_delay].
+    ///
+    /// Each connection subscribes with the access token of this [OwnerApi]. When the service
+    /// doesn't accept it, the stream ends after [StreamDisconnect::Unauthorized]. Refresh the
+    /// token with [OwnerApi::refresh()] and start a new stream.
+    pub async fn stream_vehicle(
+        &self,
+        vehicle_id: &VehicleId,
+    ) -> Result<VehicleStream, TeslatteError> {
+        self.stream_vehicle_with_config(vehicle_id, StreamingConfig::default())
+            .await
+    }
+
+    pub async fn stream_vehicle_with_config(
+        &self,
+        vehicle_id: &VehicleId,
+        config: StreamingConfig,
+    ) -> Result<VehicleStream, TeslatteError> {
+        check_scope!(self, stream_vehicle, [VehicleDeviceData])?;
+        // The service knows the vehicle by its `vehicle_id`, not the `id` used by the API.
+        let vehicle = self.vehicle(vehicle_id).await?;
+        let tag = vehicle.vehicle_id.0.to_string();
+
+        let (tx, events) = mpsc::channel(config.buffer);
+        let token = self.access_token.expose().to_string();
+        let task = tokio::spawn(reconnect(token, tag, connector()?, config, tx));
+        Ok(VehicleStream { events, task })
+    }
+}
+
+fn connector() -> Result<Connector, TeslatteError> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| TeslatteError::StreamingTlsError(e.to_string()))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Connector::Rustls(Arc::new(config)))
+}
+
+#[derive(Debug, Clone)]
+pub struct StreamingConfig {
+    pub url: String,
+    /// Reconnect when nothing is received for this long.
+    pub timeout: Duration,
+    /// How long to wait before reconnecting.
+    pub reconnect_delay: Duration,
+    /// Events to keep when they aren't being read, before the connection waits.
+    pub buffer: usize,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            url: "wss://streaming.vn.teslamotors.com/streaming/".to_string(),
+            timeout: Duration::from_secs(30),
+            reconnect_delay: Duration::from_secs(5),
+            buffer: 256,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    Data(StreamData),
+    /// The connection was lost, and the stream is about to reconnect. After
+    /// [StreamDisconnect::Unauthorized], the stream ends instead.
+    Disconnected(StreamDisconnect),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamDisconnect {
+    /// The service sent `data:error`, e.g. `vehicle_disconnected` when the vehicle is asleep.
+    DataError { error_type: String, value: String },
+    /// The service sent a `client_error`, because it didn't accept the access token.
+    Unauthorized(String),
+    /// Nothing was received for [StreamingConfig::timeout].
+    Timeout,
+    /// The service closed the websocket.
+    Closed,
+    /// The websocket couldn't connect, or failed.
+    Connection(String),
+}
+
+/// One update from the vehicle. Values are `None` when the vehicle didn't send them, e.g.
+/// `speed` while parked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamData {
+    pub timestamp: DateTime<Utc>,
+    /// mph.
+    pub speed: Option<f64>,
+    /// Miles.
+    pub odometer: Option<f64>,
+    /// Percent.
+    pub soc: Option<i64>,
+    /// Meters.
+    pub elevation: Option<i64>,
+    pub est_heading: Option<i64>,
+    pub est_lat: Option<f64>,
+    pub est_lng: Option<f64>,
+    /// kW. Negative while regenerating or charging.
+    pub power: Option<i64>,
+    /// The same values as [DriveState::shift_state](crate::vehicles::DriveState::shift_state).
+    pub shift_state: Option<String>,
+    /// Rated range in miles.
+    pub range: Option<i64>,
+    /// Estimated range in miles.
+    pub est_range: Option<i64>,
+    pub heading: Option<i64>,
+}
+
+impl FromStr for StreamData {
+    type Err = TeslatteError;
+
+    /// Parse the `value` of a `data:update`, e.g. `1704067200000,25,12345.6,80,...`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || TeslatteError::DecodeStreamDataError(s.to_string());
+        let values = s.split(',').collect::<Vec<_>>();
+        if values.len() != COLUMNS.split(',').count() + 1 {
+            return Err(error());
+        }
+
+        fn column<T: FromStr>(s: &str, value: &str) -> Result<Option<T>, TeslatteError> {
+            if value.is_empty() {
+                return Ok(None);
+            }
+            let value = value
+                .parse()
+                .map_err(|_| TeslatteError::DecodeStreamDataError(s.to_string()))?;
+            Ok(Some(value))
+        }
+
+        let timestamp = values[0].parse().map_err(|_| error())?;
+        Ok(StreamData {
+            timestamp: DateTime::from_timestamp_millis(timestamp).ok_or_else(error)?,
+            speed: column(s, values[1])?,
+            odometer: column(s, values[2])?,
+            soc: column(s, values[3])?,
+            elevation: column(s, values[4])?,
+            est_heading: column(s, values[5])?,
+            est_lat: column(s, values[6])?,
+            est_lng: column(s, values[7])?,
+            power: column(s, values[8])?,
+            shift_state: column(s, values[9])?,
+            range: column(s, values[10])?,
+            est_range: column(s, values[11])?,
+            heading: column(s, values[12])?,
+        })
+    }
+}
+
+/// A [Stream] of [StreamEvent]s, which only ends when the access token isn't accepted. It
+/// disconnects when dropped.
+#[derive(Debug)]
+pub struct VehicleStream {
+    events: mpsc::Receiver<StreamEvent>,
+    task: JoinHandle<()>,
+}
+
+impl Stream for VehicleStream {
+    type Item = StreamEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+impl Drop for VehicleStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[derive(Serialize)]
+struct Subscribe<'a> {
+    msg_type: &'static str,
+    token: &'a str,
+    value: &'static str,
+    tag: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamMessage {
+    msg_type: String,
+    value: Option<String>,
+    error_type: Option<String>,
+}
+
+async fn reconnect(
+    token: String,
+    tag: String,
+    connector: Connector,
+    config: StreamingConfig,
+    tx: mpsc::Sender<StreamEvent>,
+) {
+    let subscribe = serde_json::to_string(&Subscribe {
+        msg_type: "data:subscribe_oauth",
+        token: &token,
+        value: COLUMNS,
+        tag: &tag,
+    })
+    .expect("subscribe message is always serializable");
+    loop {
+        let disconnect = match receive(&subscribe, &connector, &config, &tx).await {
+            Ok(()) => return,
+            Err(disconnect) => disconnect,
+        };
+        debug!("Streaming disconnected: {disconnect:?}");
+        let unauthorized = matches!(disconnect, StreamDisconnect::Unauthorized(_));
+        // Refreshing here would use up the caller's refresh token, so leave it to them.
+        if tx
+            .send(StreamEvent::Disconnected(disconnect))
+            .await
+            .is_err()
+            || unauthorized
+        {
+            return;
+        }
+        tokio::time::sleep(config.reconnect_delay).await;
+    }
+}
+
+/// Returns `Ok` when the [VehicleStream] was dropped.
+async fn receive(
+    subscribe: &str,
+    connector: &Connector,
+    config: &StreamingConfig,
+    tx: &mpsc::Sender<StreamEvent>,
+) -> Result<(), StreamDisconnect> {
+    let connection_error =
+        |e: tokio_tungstenite::tungstenite::Error| StreamDisconnect::Connection(e.to_string());
+    let (mut websocket, _) = tokio_tungstenite::connect_async_tls_with_config(
+        &config.url,
+        None,
+        false,
+        Some(connector.clone()),
+    )
+    .await
+    .map_err(connection_error)?;
+    websocket
+        .send(Message::Text(subscribe.to_string()))
+        .await
+        .map_err(connection_error)?;
+
+    loop {
+        let message = tokio::time::timeout(config.timeout, websocket.next())
+            .await
+            .map_err(|_| StreamDisconnect::Timeout)?
+            .ok_or(StreamDisconnect::Closed)?
+            .map_err(connection_error)?;
+        // The service sends JSON in binary messages.
+        let text = match message {
+            Message::Binary(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Message::Text(text) => text,
+            Message::Close(_) => return Err(StreamDisconnect::Closed),
+            _ => continue,
+        };
+        let message = match serde_json::from_str::<StreamMessage>(&text) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Streaming message {text}: {e}");
+                continue;
+            }
+        };
+
+        match message.msg_type.as_str() {
+            "data:update" => {
+                let value = message.value.unwrap_or_default();
+                match value.parse() {
+                    Ok(data) => {
+                        if tx.send(StreamEvent::Data(data)).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                    Err(e) => warn!("{e}"),
+                }
+            }
+            "data:error" if message.error_type.as_deref() == Some("client_error") => {
+                return Err(StreamDisconnect::Unauthorized(
+                    message.value.unwrap_or_default(),
+                ));
+            }
+            "data:error" => {
+                return Err(StreamDisconnect::DataError {
+                    error_type: message.error_type.unwrap_or_default(),
+                    value: message.value.unwrap_or_default(),
+                });
+            }
+            msg_type => debug!("Streaming message {msg_type}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AccessToken, RefreshToken};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const UPDATE: &str = "1704067200000,25,12345.6,80,100,180,-37.8,144.9,12,D,200,180,181";
+
+    #[test]
+    fn parse_stream_data() {
+        let data: StreamData = UPDATE.parse().unwrap();
+        assert_eq!(data.timestamp.timestamp(), 1704067200);
+        assert_eq!(data.speed, Some(25.0));
+        assert_eq!(data.odometer, Some(12345.6));
+        assert_eq!(data.shift_state.as_deref(), Some("D"));
+        assert_eq!(data.heading, Some(181));
+
+        let parked: StreamData = "1704067200000,,12345.6,80,100,180,-37.8,144.9,0,,200,180,181"
+            .parse()
+            .unwrap();
+        assert_eq!(parked.speed, None);
+        assert_eq!(parked.shift_state, None);
+
+        assert!("1704067200000,25".parse::<StreamData>().is_err());
+        assert!(UPDATE.replace("25", "fast").parse::<StreamData>().is_err());
+    }
+
+    /// Stands in for the API, with vehicle 1 streaming as 1234.
+    async fn api_stand_in() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/1", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let n = stream.read(&mut request).await.unwrap();
+                assert!(request[..n].starts_with(b"GET /api/1/vehicles/1 "));
+                let body = serde_json::json!({
+                    "response": {
+                        "id": 1,
+                        "vehicle_id": 1234,
+                        "vin": "5YJ3000000NEXUS01",
+                        "display_name": null,
+                        "state": "online",
+                        "in_service": false
+                    }
+                })
+                .to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        url
+    }
+
+    /// Stands in for the streaming service. Sends an update and then `data:error` on the first
+    /// connection, and an update and then a `client_error` on the second.
+    async fn stand_in() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/streaming/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            for connection in 0..2 {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut websocket = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let subscribe = websocket.next().await.unwrap().unwrap();
+                let subscribe: serde_json::Value =
+                    serde_json::from_str(subscribe.to_text().unwrap()).unwrap();
+                assert_eq!(subscribe["msg_type"], "data:subscribe_oauth");
+                assert_eq!(subscribe["token"], "token");
+                assert_eq!(subscribe["tag"], "1234");
+
+                let send = |json: serde_json::Value| Message::Binary(json.to_string().into_bytes());
+                websocket
+                    .send(send(serde_json::json!({
+                        "msg_type": "control:hello",
+                        "connection_timeout": 30000
+                    })))
+                    .await
+                    .unwrap();
+                websocket
+                    .send(send(serde_json::json!({
+                        "msg_type": "data:update",
+                        "tag": "1234",
+                        "value": UPDATE
+                    })))
+                    .await
+                    .unwrap();
+                let error = if connection == 0 {
+                    serde_json::json!({
+                        "msg_type": "data:error",
+                        "tag": "1234",
+                        "value": "disconnected",
+                        "error_type": "vehicle_disconnected"
+                    })
+                } else {
+                    serde_json::json!({
+                        "msg_type": "data:error",
+                        "tag": "1234",
+                        "value": "Can't validate token. ",
+                        "error_type": "client_error"
+                    })
+                };
+                websocket.send(send(error)).await.unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn reconnects_after_data_error() {
+        let mut api = OwnerApi::new(
+            AccessToken::new("token"),
+            Some(RefreshToken::new("refresh_token")),
+        );
+        api.api_url = api_stand_in().await;
+        let config = StreamingConfig {
+            url: stand_in().await,
+            reconnect_delay: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let mut stream = api
+            .stream_vehicle_with_config(&VehicleId::new(1), config)
+            .await
+            .unwrap();
+
+        assert!(matches!(stream.next().await, Some(StreamEvent::Data(_))));
+        assert_eq!(
+            stream.next().await,
+            Some(StreamEvent::Disconnected(StreamDisconnect::DataError {
+                error_type: "vehicle_disconnected".to_string(),
+                value: "disconnected".to_string()
+            }))
+        );
+        let Some(StreamEvent::Data(data)) = stream.next().await else {
+            panic!("expected data after reconnecting");
+        };
+        assert_eq!(data.soc, Some(80));
+
+        // The stream ends even with a refresh token, which is left to the caller.
+        assert_eq!(
+            stream.next().await,
+            Some(StreamEvent::Disconnected(StreamDisconnect::Unauthorized(
+                "Can't validate token. ".to_string()
+            )))
+        );
+        assert_eq!(stream.next().await, None);
+    }
+}
@@ -0,0 +1,745 @@
+//! A server that receives Fleet Telemetry from vehicles. Needs the `telemetry` feature.
+//!
+//! Vehicles connect with mutual TLS and a websocket, and send protobuf records in a FlatBuffers
+//! [StreamMessage], which the server acknowledges. Configure them to
+//! connect with [OwnerApi::fleet_telemetry_config_create()](crate::OwnerApi::fleet_telemetry_config_create).
+//!
+//! ```no_run
+//! # use futures_util::StreamExt;
+//! # use teslatte::telemetry::{TelemetryServer, TelemetryServerConfig, TelemetryTls};
+//! # async fn run() -> Result<(), teslatte::error::TeslatteError> {
+//! let tls = TelemetryTls::from_pem_files("server.pem", "server.key", "vehicle_ca.pem")?;
+//! let config = TelemetryServerConfig::new("0.0.0.0:443".parse().unwrap()).tls(tls);
+//! let mut server = TelemetryServer::bind(config).await?;
+//! while let Some(record) = server.next().await {
+//!     println!("{}: {:?}", record.vin(), record);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+pub mod message;
+pub mod proto;
+
+use crate::error::TeslatteError;
+use crate::fleet_telemetry::TelemetryField;
+use crate::telemetry::message::StreamMessage;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, Stream, StreamExt};
+use prost::Message as _;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{self, RootCertStore, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, warn};
+
+/// The field of a [TelemetryValue].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TelemetryKey {
+    Field(TelemetryField),
+    /// A field teslatte doesn't know the number of, e.g. one added in newer firmware.
+    Other(i32),
+}
+
+impl TelemetryKey {
+    pub fn from_number(number: i32) -> Self {
+        TelemetryField::from_number(number)
+            .map(TelemetryKey::Field)
+            .unwrap_or(TelemetryKey::Other(number))
+    }
+}
+
+impl Display for TelemetryKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TelemetryKey::Field(field) => write!(f, "{field}"),
+            TelemetryKey::Other(number) => write!(f, "Field{number}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TelemetryValue {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Location {
+        latitude: f64,
+        longitude: f64,
+    },
+    /// The same values as [ChargeState::charging_state](crate::vehicles::ChargeState::charging_state),
+    /// e.g. `Charging`.
+    ChargingState(String),
+    /// The same values as [DriveState::shift_state](crate::vehicles::DriveState::shift_state),
+    /// e.g. `D`.
+    ShiftState(String),
+    /// The vehicle doesn't have a value for the field right now.
+    Invalid,
+}
+
+impl From<proto::Value> for TelemetryValue {
+    fn from(value: proto::Value) -> Self {
+        use proto::Kind;
+        match value.value {
+            Some(Kind::StringValue(s)) => TelemetryValue::String(s),
+            Some(Kind::IntValue(i)) => TelemetryValue::Int(i.into()),
+            Some(Kind::LongValue(i)) => TelemetryValue::Int(i),
+            Some(Kind::FloatValue(f)) => TelemetryValue::Float(f.into()),
+            Some(Kind::DoubleValue(f)) => TelemetryValue::Float(f),
+            Some(Kind::BooleanValue(b)) => TelemetryValue::Bool(b),
+            Some(Kind::LocationValue(l)) => TelemetryValue::Location {
+                latitude: l.latitude,
+                longitude: l.longitude,
+            },
+            Some(Kind::ChargingValue(n)) => {
+                let state = match n {
+                    1 => "Disconnected",
+                    2 => "NoPower",
+                    3 => "Starting",
+                    4 => "Charging",
+                    5 => "Complete",
+                    6 => "Stopped",
+                    _ => "Unknown",
+                };
+                TelemetryValue::ChargingState(state.to_string())
+            }
+            Some(Kind::ShiftStateValue(n)) => {
+                let state = match n {
+                    1 => "Invalid",
+                    2 => "P",
+                    3 => "R",
+                    4 => "N",
+                    5 => "D",
+                    6 => "SNA",
+                    _ => "Unknown",
+                };
+                TelemetryValue::ShiftState(state.to_string())
+            }
+            Some(Kind::Invalid(_)) | None => TelemetryValue::Invalid,
+        }
+    }
+}
+
+impl Display for TelemetryValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TelemetryValue::String(s) => write!(f, "{s}"),
+            TelemetryValue::Int(i) => write!(f, "{i}"),
+            TelemetryValue::Float(x) => write!(f, "{x}"),
+            TelemetryValue::Bool(b) => write!(f, "{b}"),
+            TelemetryValue::Location {
+                latitude,
+                longitude,
+            } => write!(f, "{latitude},{longitude}"),
+            TelemetryValue::ChargingState(s) | TelemetryValue::ShiftState(s) => write!(f, "{s}"),
+            TelemetryValue::Invalid => write!(f, "invalid"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TelemetryRecord {
+    Data(TelemetryData),
+    Alerts(TelemetryAlerts),
+    Errors(TelemetryErrors),
+}
+
+impl TelemetryRecord {
+    /// Decode a websocket message from a vehicle.
+    pub fn decode(message: &[u8]) -> Result<Self, TeslatteError> {
+        Self::from_message(&StreamMessage::decode(message)?)
+    }
+
+    /// Decode the payload of a [StreamMessage], depending on its topic.
+    pub fn from_message(message: &StreamMessage) -> Result<Self, TeslatteError> {
+        let payload = message.payload.as_slice();
+        match message.topic.as_str() {
+            "V" => {
+                let payload =
+                    proto::Payload::decode(payload).map_err(TeslatteError::DecodeTelemetryError)?;
+                Ok(TelemetryRecord::Data(payload.into()))
+            }
+            "alerts" => {
+                let alerts = proto::VehicleAlerts::decode(payload)
+                    .map_err(TeslatteError::DecodeTelemetryError)?;
+                Ok(TelemetryRecord::Alerts(alerts.into()))
+            }
+            "errors" => {
+                let errors = proto::VehicleErrors::decode(payload)
+                    .map_err(TeslatteError::DecodeTelemetryError)?;
+                Ok(TelemetryRecord::Errors(errors.into()))
+            }
+            topic => Err(TeslatteError::UnknownTelemetryTopic(topic.to_string())),
+        }
+    }
+
+    pub fn vin(&self) -> &str {
+        match self {
+            TelemetryRecord::Data(data) => &data.vin,
+            TelemetryRecord::Alerts(alerts) => &alerts.vin,
+            TelemetryRecord::Errors(errors) => &errors.vin,
+        }
+    }
+}
+
+/// Values of the fields configured with
+/// [FleetTelemetryConfig::field()](crate::fleet_telemetry::FleetTelemetryConfig::field).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryData {
+    pub vin: String,
+    pub created_at: Option<DateTime<Utc>>,
+    /// The vehicle sent this again, because the server didn't acknowledge it in time.
+    pub is_resend: bool,
+    pub values: BTreeMap<TelemetryKey, TelemetryValue>,
+}
+
+impl TelemetryData {
+    pub fn get(&self, field: TelemetryField) -> Option<&TelemetryValue> {
+        self.values.get(&TelemetryKey::Field(field))
+    }
+}
+
+impl From<proto::Payload> for TelemetryData {
+    fn from(payload: proto::Payload) -> Self {
+        let values = payload
+            .data
+            .into_iter()
+            .map(|datum| {
+                let value = datum.value.map(TelemetryValue::from);
+                (
+                    TelemetryKey::from_number(datum.key),
+                    value.unwrap_or(TelemetryValue::Invalid),
+                )
+            })
+            .collect();
+        TelemetryData {
+            vin: payload.vin,
+            created_at: timestamp(payload.created_at),
+            is_resend: payload.is_resend,
+            values,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryAlerts {
+    pub vin: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub alerts: Vec<TelemetryAlert>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryAlert {
+    pub name: String,
+    /// Who the alert is for, e.g. `customer` or `service-fix`, like
+    /// [RecentAlert::audience](crate::vehicle_info::RecentAlert::audience).
+    pub audiences: Vec<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    /// Not set while the alert is active.
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl From<proto::VehicleAlerts> for TelemetryAlerts {
+    fn from(alerts: proto::VehicleAlerts) -> Self {
+        TelemetryAlerts {
+            vin: alerts.vin,
+            created_at: timestamp(alerts.created_at),
+            alerts: alerts
+                .alerts
+                .into_iter()
+                .map(|alert| TelemetryAlert {
+                    name: alert.name,
+                    audiences: alert
+                        .audiences
+                        .into_iter()
+                        .map(|a| {
+                            match a {
+                                1 => "customer",
+                                2 => "service",
+                                3 => "service-fix",
+                                _ => "unknown",
+                            }
+                            .to_string()
+                        })
+                        .collect(),
+                    started_at: timestamp(alert.started_at),
+                    ended_at: timestamp(alert.ended_at),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Problems the vehicle had, e.g. with its telemetry config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryErrors {
+    pub vin: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub errors: Vec<TelemetryVehicleError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryVehicleError {
+    pub name: String,
+    pub body: String,
+    pub tags: HashMap<String, String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<proto::VehicleErrors> for TelemetryErrors {
+    fn from(errors: proto::VehicleErrors) -> Self {
+        TelemetryErrors {
+            vin: errors.vin,
+            created_at: timestamp(errors.created_at),
+            errors: errors
+                .errors
+                .into_iter()
+                .map(|error| TelemetryVehicleError {
+                    name: error.name,
+                    body: error.body,
+                    tags: error.tags,
+                    created_at: timestamp(error.created_at),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn timestamp(timestamp: Option<proto::Timestamp>) -> Option<DateTime<Utc>> {
+    let timestamp = timestamp?;
+    DateTime::from_timestamp(timestamp.seconds, u32::try_from(timestamp.nanos).ok()?)
+}
+
+/// The latest value of each field, by VIN.
+#[derive(Debug, Clone, Default)]
+pub struct TelemetryState {
+    vehicles: HashMap<String, BTreeMap<TelemetryKey, TelemetryValue>>,
+}
+
+impl TelemetryState {
+    /// Keep the values of a [TelemetryRecord::Data]. Other records are ignored.
+    pub fn apply(&mut self, record: &TelemetryRecord) {
+        if let TelemetryRecord::Data(data) = record {
+            let values = self.vehicles.entry(data.vin.clone()).or_default();
+            for (key, value) in &data.values {
+                values.insert(*key, value.clone());
+            }
+        }
+    }
+
+    pub fn vehicle(&self, vin: &str) -> Option<&BTreeMap<TelemetryKey, TelemetryValue>> {
+        self.vehicles.get(vin)
+    }
+
+    pub fn get(&self, vin: &str, field: TelemetryField) -> Option<&TelemetryValue> {
+        self.vehicle(vin)?.get(&TelemetryKey::Field(field))
+    }
+
+    pub fn vins(&self) -> impl Iterator<Item = &str> {
+        self.vehicles.keys().map(String::as_str)
+    }
+}
+
+/// The server certificate, and the CA that signed the vehicles' client certificates.
+#[derive(Debug, Clone)]
+pub struct TelemetryTls {
+    server_config: Arc<ServerConfig>,
+}
+
+impl TelemetryTls {
+    pub fn from_pem(
+        cert_chain: &[u8],
+        private_key: &[u8],
+        client_ca: &[u8],
+    ) -> Result<Self, TeslatteError> {
+        let cert_chain = rustls_pemfile::certs(&mut &cert_chain[..])
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(TeslatteError::TelemetryIoError)?;
+        let private_key = rustls_pemfile::private_key(&mut &private_key[..])
+            .map_err(TeslatteError::TelemetryIoError)?
+            .ok_or_else(|| TeslatteError::TelemetryTlsError("no private key".to_string()))?;
+
+        let mut roots = RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut &client_ca[..]) {
+            let cert = cert.map_err(TeslatteError::TelemetryIoError)?;
+            roots.add(cert).map_err(tls_error)?;
+        }
+
+        let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+        let verifier =
+            WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+                .build()
+                .map_err(tls_error)?;
+        let server_config = ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(cert_chain, private_key)
+            .map_err(tls_error)?;
+
+        Ok(Self {
+            server_config: Arc::new(server_config),
+        })
+    }
+
+    pub fn from_pem_files(
+        cert_chain: impl AsRef<Path>,
+        private_key: impl AsRef<Path>,
+        client_ca: impl AsRef<Path>,
+    ) -> Result<Self, TeslatteError> {
+        let read = |path: &Path| std::fs::read(path).map_err(TeslatteError::TelemetryIoError);
+        Self::from_pem(
+            &read(cert_chain.as_ref())?,
+            &read(private_key.as_ref())?,
+            &read(client_ca.as_ref())?,
+        )
+    }
+}
+
+fn tls_error(e: impl Display) -> TeslatteError {
+    TeslatteError::TelemetryTlsError(e.to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryServerConfig {
+    pub addr: SocketAddr,
+    /// Without TLS, vehicles can't connect. Only leave it out behind a proxy that terminates
+    /// TLS, or for testing.
+    pub tls: Option<TelemetryTls>,
+    /// Records to keep when they aren't being read, before connections wait.
+    pub buffer: usize,
+}
+
+impl TelemetryServerConfig {
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            tls: None,
+            buffer: 1024,
+        }
+    }
+
+    pub fn tls(mut self, tls: TelemetryTls) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+}
+
+/// A [Stream] of the records from every connected vehicle. The server stops when this is
+/// dropped.
+#[derive(Debug)]
+pub struct TelemetryServer {
+    local_addr: SocketAddr,
+    records: mpsc::Receiver<TelemetryRecord>,
+    task: JoinHandle<()>,
+}
+
+impl TelemetryServer {
+    pub async fn bind(config: TelemetryServerConfig) -> Result<Self, TeslatteError> {
+        let listener = TcpListener::bind(config.addr)
+            .await
+            .map_err(TeslatteError::TelemetryIoError)?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(TeslatteError::TelemetryIoError)?;
+        let acceptor = config.tls.map(|tls| TlsAcceptor::from(tls.server_config));
+        let (tx, records) = mpsc::channel(config.buffer);
+        let task = tokio::spawn(accept(listener, acceptor, tx));
+        Ok(Self {
+            local_addr,
+            records,
+            task,
+        })
+    }
+
+    /// Useful when binding to port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Stream for TelemetryServer {
+    type Item = TelemetryRecord;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.records.poll_recv(cx)
+    }
+}
+
+impl Drop for TelemetryServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn accept(
+    listener: TcpListener,
+    acceptor: Option<TlsAcceptor>,
+    tx: mpsc::Sender<TelemetryRecord>,
+) {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("Accepting a telemetry connection: {e}");
+                continue;
+            }
+        };
+        debug!("Telemetry connection from {peer}");
+        let acceptor = acceptor.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let result = match acceptor {
+                Some(acceptor) => match acceptor.accept(stream).await {
+                    Ok(stream) => receive(stream, tx).await,
+                    Err(e) => Err(e.to_string()),
+                },
+                None => receive(stream, tx).await,
+            };
+            if let Err(e) = result {
+                warn!("Telemetry connection from {peer}: {e}");
+            }
+        });
+    }
+}
+
+async fn receive<S>(stream: S, tx: mpsc::Sender<TelemetryRecord>) -> Result<(), String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut websocket = tokio_tungstenite::accept_async(stream)
+        .await
+        .map_err(|e| e.to_string())?;
+    while let Some(message) = websocket.next().await {
+        match message.map_err(|e| e.to_string())? {
+            Message::Binary(bytes) => {
+                let message = match StreamMessage::decode(&bytes) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        warn!("{e}");
+                        continue;
+                    }
+                };
+                // Otherwise the vehicle sends the message again.
+                websocket
+                    .send(Message::Binary(message.ack()))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                match TelemetryRecord::from_message(&message) {
+                    Ok(record) => {
+                        if tx.send(record).await.is_err() {
+                            // The server was dropped.
+                            return Ok(());
+                        }
+                    }
+                    Err(e) => warn!("{e}"),
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::SinkExt;
+
+    /// A `V` message with Soc, Location, Gear and a field teslatte doesn't know, in the
+    /// FlatBuffers envelope of the vehicle.
+    const DATA_MESSAGE: &[u8] = &[
+        0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0e, 0x00, 0x18, 0x00, 0x14, 0x00, 0x10, 0x00, 0x07,
+        0x00, 0x0c, 0x00, 0x08, 0x00, 0x0e, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x10, 0x00,
+        0x00, 0x00, 0x58, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00, 0x05,
+        0x00, 0x00, 0x00, 0x6d, 0x73, 0x67, 0x2d, 0x31, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+        0x56, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00, 0x33, 0x62, 0x36, 0x65, 0x38, 0x61, 0x33,
+        0x66, 0x2d, 0x31, 0x66, 0x30, 0x63, 0x2d, 0x34, 0x66, 0x35, 0x65, 0x2d, 0x39, 0x66, 0x33,
+        0x61, 0x2d, 0x36, 0x63, 0x31, 0x64, 0x32, 0x65, 0x34, 0x62, 0x35, 0x61, 0x36, 0x39, 0x10,
+        0x00, 0x24, 0x00, 0x04, 0x00, 0x14, 0x00, 0x10, 0x00, 0x0c, 0x00, 0x08, 0x00, 0x18, 0x00,
+        0x10, 0x00, 0x00, 0x00, 0x80, 0x00, 0x92, 0x65, 0x1c, 0x00, 0x00, 0x00, 0x30, 0x00, 0x00,
+        0x00, 0x40, 0x00, 0x00, 0x00, 0x98, 0x00, 0x00, 0x00, 0x7b, 0xf4, 0x51, 0xc2, 0x8c, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x00, 0x00, 0x00, 0x35, 0x59, 0x4a, 0x33, 0x30,
+        0x30, 0x30, 0x30, 0x30, 0x30, 0x4e, 0x45, 0x58, 0x55, 0x53, 0x30, 0x31, 0x00, 0x00, 0x00,
+        0x0e, 0x00, 0x00, 0x00, 0x76, 0x65, 0x68, 0x69, 0x63, 0x6c, 0x65, 0x5f, 0x64, 0x65, 0x76,
+        0x69, 0x63, 0x65, 0x00, 0x00, 0x56, 0x00, 0x00, 0x00, 0x0a, 0x0d, 0x08, 0x08, 0x12, 0x09,
+        0x29, 0x00, 0x00, 0x00, 0x00, 0x00, 0x60, 0x54, 0x40, 0x0a, 0x18, 0x08, 0x15, 0x12, 0x14,
+        0x3a, 0x12, 0x09, 0x66, 0x66, 0x66, 0x66, 0x66, 0xe6, 0x42, 0xc0, 0x11, 0xcd, 0xcc, 0xcc,
+        0xcc, 0xcc, 0x1c, 0x62, 0x40, 0x0a, 0x06, 0x08, 0x0a, 0x12, 0x02, 0x48, 0x05, 0x0a, 0x08,
+        0x08, 0xe7, 0x07, 0x12, 0x03, 0x0a, 0x01, 0x78, 0x12, 0x06, 0x08, 0x80, 0x81, 0xc8, 0xac,
+        0x06, 0x1a, 0x11, 0x35, 0x59, 0x4a, 0x33, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x4e, 0x45,
+        0x58, 0x55, 0x53, 0x30, 0x31, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x76, 0x65, 0x68, 0x69,
+        0x63, 0x6c, 0x65, 0x5f, 0x64, 0x65, 0x76, 0x69, 0x63, 0x65, 0x2e, 0x35, 0x59, 0x4a, 0x33,
+        0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x4e, 0x45, 0x58, 0x55, 0x53, 0x30, 0x31,
+    ];
+
+    fn alerts_message() -> Vec<u8> {
+        let alerts = proto::VehicleAlerts {
+            alerts: vec![proto::VehicleAlert {
+                name: "Name_Of_The_Alert".to_string(),
+                audiences: vec![1, 3],
+                started_at: Some(proto::Timestamp {
+                    seconds: 1704067200,
+                    nanos: 0,
+                }),
+                ended_at: None,
+            }],
+            created_at: None,
+            vin: "5YJ3000000NEXUS01".to_string(),
+        };
+        StreamMessage {
+            txid: b"txid-alerts".to_vec(),
+            topic: "alerts".to_string(),
+            device_id: "5YJ3000000NEXUS01".to_string(),
+            payload: alerts.encode_to_vec(),
+            ..Default::default()
+        }
+        .encode()
+    }
+
+    #[test]
+    fn decode_message() {
+        let message = StreamMessage::decode(DATA_MESSAGE).unwrap();
+        assert_eq!(message.txid, b"3b6e8a3f-1f0c-4f5e-9f3a-6c1d2e4b5a69");
+        assert_eq!(message.topic, "V");
+        assert_eq!(message.device_type, "vehicle_device");
+        assert_eq!(message.device_id, "5YJ3000000NEXUS01");
+        assert_eq!(message.created_at, 1704067200);
+        assert_eq!(message.delivered_at_epoch_ms, 1704067200123);
+        assert_eq!(StreamMessage::decode(&message.encode()).unwrap(), message);
+
+        let ack = StreamMessage::decode(&message.ack()).unwrap();
+        assert_eq!(ack.txid, message.txid);
+        assert_eq!(ack.topic, "V");
+        assert!(ack.payload.is_empty());
+
+        assert!(matches!(
+            StreamMessage::decode(&DATA_MESSAGE[..40]),
+            Err(TeslatteError::DecodeTelemetryMessageError(_))
+        ));
+    }
+
+    #[test]
+    fn decode_data() {
+        let record = TelemetryRecord::decode(DATA_MESSAGE).unwrap();
+        let TelemetryRecord::Data(data) = record else {
+            panic!("{record:?}");
+        };
+        assert_eq!(data.vin, "5YJ3000000NEXUS01");
+        assert_eq!(data.created_at.unwrap().timestamp(), 1704067200);
+        assert_eq!(
+            data.get(TelemetryField::Soc),
+            Some(&TelemetryValue::Float(81.5))
+        );
+        assert_eq!(
+            data.get(TelemetryField::Location),
+            Some(&TelemetryValue::Location {
+                latitude: -37.8,
+                longitude: 144.9
+            })
+        );
+        assert_eq!(
+            data.get(TelemetryField::Gear),
+            Some(&TelemetryValue::ShiftState("D".to_string()))
+        );
+        assert_eq!(
+            data.values[&TelemetryKey::Other(999)],
+            TelemetryValue::String("x".to_string())
+        );
+    }
+
+    #[test]
+    fn every_field_has_a_value() {
+        use strum::IntoEnumIterator;
+
+        let payload = proto::Payload {
+            data: TelemetryField::iter()
+                .map(|field| proto::Datum {
+                    key: field.number(),
+                    value: Some(proto::Value {
+                        value: Some(proto::Kind::StringValue(field.to_string())),
+                    }),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let data = TelemetryData::from(payload);
+        for field in TelemetryField::iter() {
+            assert_eq!(
+                data.get(field),
+                Some(&TelemetryValue::String(field.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn decode_alerts_and_unknown_topic() {
+        let record = TelemetryRecord::decode(&alerts_message()).unwrap();
+        let TelemetryRecord::Alerts(alerts) = record else {
+            panic!("{record:?}");
+        };
+        assert_eq!(alerts.alerts[0].audiences, ["customer", "service-fix"]);
+        assert!(alerts.alerts[0].ended_at.is_none());
+
+        let message = StreamMessage {
+            topic: "connectivity".to_string(),
+            ..Default::default()
+        }
+        .encode();
+        assert!(matches!(
+            TelemetryRecord::decode(&message),
+            Err(TeslatteError::UnknownTelemetryTopic(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn server_replays_records() {
+        let config = TelemetryServerConfig::new("127.0.0.1:0".parse().unwrap());
+        let mut server = TelemetryServer::bind(config).await.unwrap();
+
+        let url = format!("ws://{}/", server.local_addr());
+        let (mut client, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        client
+            .send(Message::Binary(DATA_MESSAGE.to_vec()))
+            .await
+            .unwrap();
+        // Bad messages are skipped.
+        client.send(Message::Binary(vec![0xff])).await.unwrap();
+        client
+            .send(Message::Binary(alerts_message()))
+            .await
+            .unwrap();
+
+        // Each message that could be decoded is acknowledged.
+        for txid in [&b"3b6e8a3f-1f0c-4f5e-9f3a-6c1d2e4b5a69"[..], b"txid-alerts"] {
+            let Some(Ok(Message::Binary(ack))) = client.next().await else {
+                panic!("no ack");
+            };
+            assert_eq!(StreamMessage::decode(&ack).unwrap().txid, txid);
+        }
+        client.close(None).await.unwrap();
+
+        let mut state = TelemetryState::default();
+        let data = server.next().await.unwrap();
+        state.apply(&data);
+        let alerts = server.next().await.unwrap();
+        state.apply(&alerts);
+        assert!(matches!(alerts, TelemetryRecord::Alerts(_)));
+        assert_eq!(
+            state.get("5YJ3000000NEXUS01", TelemetryField::Soc),
+            Some(&TelemetryValue::Float(81.5))
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! The FlatBuffers envelope around each websocket message from a vehicle, from the Fleet
+//! Telemetry `messages/tesla` schema, written out by hand so the build doesn't need `flatc`.
+//!
+//! ```text
+//! table FlatbuffersStream {
+//!   createdAt: uint32;
+//!   senderId: [ubyte];
+//!   payload: [ubyte];
+//!   deviceType: [ubyte];
+//!   deviceId: [ubyte];
+//!   deliveredAtEpochMs: uint64;
+//! }
+//!
+//! union Message { FlatbuffersStream = 4 }
+//!
+//! table FlatbuffersEnvelope {
+//!   txid: [ubyte];
+//!   topic: [ubyte];
+//!   message: Message;
+//!   messageId: [ubyte];
+//! }
+//! ```
+use crate::error::TeslatteError;
+use flatbuffers::{
+    FlatBufferBuilder, Follow, ForwardsUOffset, InvalidFlatbuffer, Table, VOffsetT, Vector,
+    Verifiable, Verifier,
+};
+
+/// The `Message` union value of a `FlatbuffersStream`.
+const MESSAGE_FLATBUFFERS_STREAM: u8 = 4;
+
+/// A message from a vehicle. The server acknowledges each one with [StreamMessage::ack()].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamMessage {
+    /// Identifies the message in its acknowledgement.
+    pub txid: Vec<u8>,
+    /// `V`, `alerts`, `errors` or `connectivity`.
+    pub topic: String,
+    pub message_id: Vec<u8>,
+    /// Seconds since the epoch.
+    pub created_at: u32,
+    pub sender_id: Vec<u8>,
+    /// e.g. `vehicle_device`.
+    pub device_type: String,
+    /// The VIN.
+    pub device_id: String,
+    pub delivered_at_epoch_ms: u64,
+    /// The protobuf record, depending on the topic.
+    pub payload: Vec<u8>,
+}
+
+impl StreamMessage {
+    pub fn decode(message: &[u8]) -> Result<Self, TeslatteError> {
+        let envelope = flatbuffers::root::<Envelope>(message).map_err(decode_error)?;
+        if envelope.message_type() != MESSAGE_FLATBUFFERS_STREAM {
+            return Err(TeslatteError::DecodeTelemetryMessageError(format!(
+                "unknown message type {}",
+                envelope.message_type()
+            )));
+        }
+        let stream = envelope
+            .message()
+            .ok_or_else(|| TeslatteError::DecodeTelemetryMessageError("no message".to_string()))?;
+        Ok(Self {
+            txid: envelope.bytes(Envelope::VT_TXID).to_vec(),
+            topic: String::from_utf8_lossy(envelope.bytes(Envelope::VT_TOPIC)).into_owned(),
+            message_id: envelope.bytes(Envelope::VT_MESSAGE_ID).to_vec(),
+            created_at: stream.created_at(),
+            sender_id: stream.bytes(Stream::VT_SENDER_ID).to_vec(),
+            device_type: String::from_utf8_lossy(stream.bytes(Stream::VT_DEVICE_TYPE)).into_owned(),
+            device_id: String::from_utf8_lossy(stream.bytes(Stream::VT_DEVICE_ID)).into_owned(),
+            delivered_at_epoch_ms: stream.delivered_at_epoch_ms(),
+            payload: stream.bytes(Stream::VT_PAYLOAD).to_vec(),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut builder = FlatBufferBuilder::new();
+        let sender_id = builder.create_vector(&self.sender_id);
+        let payload = builder.create_vector(&self.payload);
+        let device_type = builder.create_vector(self.device_type.as_bytes());
+        let device_id = builder.create_vector(self.device_id.as_bytes());
+        let start = builder.start_table();
+        builder.push_slot::<u64>(
+            Stream::VT_DELIVERED_AT_EPOCH_MS,
+            self.delivered_at_epoch_ms,
+            0,
+        );
+        builder.push_slot_always(Stream::VT_SENDER_ID, sender_id);
+        builder.push_slot_always(Stream::VT_PAYLOAD, payload);
+        builder.push_slot_always(Stream::VT_DEVICE_TYPE, device_type);
+        builder.push_slot_always(Stream::VT_DEVICE_ID, device_id);
+        builder.push_slot::<u32>(Stream::VT_CREATED_AT, self.created_at, 0);
+        let stream = builder.end_table(start);
+
+        let txid = builder.create_vector(&self.txid);
+        let topic = builder.create_vector(self.topic.as_bytes());
+        let message_id = builder.create_vector(&self.message_id);
+        let start = builder.start_table();
+        builder.push_slot_always(Envelope::VT_TXID, txid);
+        builder.push_slot_always(Envelope::VT_TOPIC, topic);
+        builder.push_slot_always(Envelope::VT_MESSAGE, stream);
+        builder.push_slot_always(Envelope::VT_MESSAGE_ID, message_id);
+        builder.push_slot::<u8>(Envelope::VT_MESSAGE_TYPE, MESSAGE_FLATBUFFERS_STREAM, 0);
+        let envelope = builder.end_table(start);
+        builder.finish(envelope, None);
+        builder.finished_data().to_vec()
+    }
+
+    /// The acknowledgement to send back, so the vehicle doesn't resend the message. It has the
+    /// same txid and topic, and nothing else.
+    pub fn ack(&self) -> Vec<u8> {
+        StreamMessage {
+            txid: self.txid.clone(),
+            topic: self.topic.clone(),
+            ..Default::default()
+        }
+        .encode()
+    }
+}
+
+fn decode_error(e: InvalidFlatbuffer) -> TeslatteError {
+    TeslatteError::DecodeTelemetryMessageError(e.to_string())
+}
+
+/// Read a `[ubyte]` field of a verified table. A missing field is empty.
+///
+/// # Safety
+///
+/// The table must have been verified, with a `[ubyte]` in the slot.
+unsafe fn bytes_field<'a>(table: &Table<'a>, slot: VOffsetT) -> &'a [u8] {
+    table
+        .get::<ForwardsUOffset<Vector<'a, u8>>>(slot, None)
+        .map(|v| v.bytes())
+        .unwrap_or_default()
+}
+
+struct Envelope<'a> {
+    table: Table<'a>,
+}
+
+impl<'a> Envelope<'a> {
+    const VT_TXID: VOffsetT = 4;
+    const VT_TOPIC: VOffsetT = 6;
+    const VT_MESSAGE_TYPE: VOffsetT = 8;
+    const VT_MESSAGE: VOffsetT = 10;
+    const VT_MESSAGE_ID: VOffsetT = 12;
+
+    fn bytes(&self, slot: VOffsetT) -> &'a [u8] {
+        // Safety: verified by `flatbuffers::root()`, and every slot passed in is a `[ubyte]`.
+        unsafe { bytes_field(&self.table, slot) }
+    }
+
+    fn message_type(&self) -> u8 {
+        // Safety: verified by `flatbuffers::root()`.
+        unsafe { self.table.get::<u8>(Self::VT_MESSAGE_TYPE, Some(0)) }.unwrap_or(0)
+    }
+
+    /// Only call when the message type is a `FlatbuffersStream`.
+    fn message(&self) -> Option<Stream<'a>> {
+        // Safety: verified by `flatbuffers::root()` as the `FlatbuffersStream` the message type
+        // says it is.
+        unsafe {
+            self.table
+                .get::<ForwardsUOffset<Table<'a>>>(Self::VT_MESSAGE, None)
+        }
+        .map(|table| Stream { table })
+    }
+}
+
+impl<'a> Follow<'a> for Envelope<'a> {
+    type Inner = Self;
+
+    unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            table: Table::new(buf, loc),
+        }
+    }
+}
+
+impl Verifiable for Envelope<'_> {
+    fn run_verifier(v: &mut Verifier, pos: usize) -> Result<(), InvalidFlatbuffer> {
+        v.visit_table(pos)?
+            .visit_field::<ForwardsUOffset<Vector<u8>>>("txid", Self::VT_TXID, false)?
+            .visit_field::<ForwardsUOffset<Vector<u8>>>("topic", Self::VT_TOPIC, false)?
+            .visit_union::<u8, _>(
+                "message_type",
+                Self::VT_MESSAGE_TYPE,
+                "message",
+                Self::VT_MESSAGE,
+                false,
+                |key, v, pos| match key {
+                    MESSAGE_FLATBUFFERS_STREAM => {
+                        v.verify_union_variant::<ForwardsUOffset<Stream>>("FlatbuffersStream", pos)
+                    }
+                    _ => Ok(()),
+                },
+            )?
+            .visit_field::<ForwardsUOffset<Vector<u8>>>("messageId", Self::VT_MESSAGE_ID, false)?
+            .finish();
+        Ok(())
+    }
+}
+
+struct Stream<'a> {
+    table: Table<'a>,
+}
+
+impl<'a> Stream<'a> {
+    const VT_CREATED_AT: VOffsetT = 4;
+    const VT_SENDER_ID: VOffsetT = 6;
+    const VT_PAYLOAD: VOffsetT = 8;
+    const VT_DEVICE_TYPE: VOffsetT = 10;
+    const VT_DEVICE_ID: VOffsetT = 12;
+    const VT_DELIVERED_AT_EPOCH_MS: VOffsetT = 14;
+
+    fn bytes(&self, slot: VOffsetT) -> &'a [u8] {
+        // Safety: verified along with the envelope, and every slot passed in is a `[ubyte]`.
+        unsafe { bytes_field(&self.table, slot) }
+    }
+
+    fn created_at(&self) -> u32 {
+        // Safety: verified along with the envelope.
+        unsafe { self.table.get::<u32>(Self::VT_CREATED_AT, Some(0)) }.unwrap_or(0)
+    }
+
+    fn delivered_at_epoch_ms(&self) -> u64 {
+        // Safety: verified along with the envelope.
+        unsafe {
+            self.table
+                .get::<u64>(Self::VT_DELIVERED_AT_EPOCH_MS, Some(0))
+        }
+        .unwrap_or(0)
+    }
+}
+
+impl<'a> Follow<'a> for Stream<'a> {
+    type Inner = Self;
+
+    unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            table: Table::new(buf, loc),
+        }
+    }
+}
+
+impl Verifiable for Stream<'_> {
+    fn run_verifier(v: &mut Verifier, pos: usize) -> Result<(), InvalidFlatbuffer> {
+        v.visit_table(pos)?
+            .visit_field::<u32>("createdAt", Self::VT_CREATED_AT, false)?
+            .visit_field::<ForwardsUOffset<Vector<u8>>>("senderId", Self::VT_SENDER_ID, false)?
+            .visit_field::<ForwardsUOffset<Vector<u8>>>("payload", Self::VT_PAYLOAD, false)?
+            .visit_field::<ForwardsUOffset<Vector<u8>>>("deviceType", Self::VT_DEVICE_TYPE, false)?
+            .visit_field::<ForwardsUOffset<Vector<u8>>>("deviceId", Self::VT_DEVICE_ID, false)?
+            .visit_field::<u64>("deliveredAtEpochMs", Self::VT_DELIVERED_AT_EPOCH_MS, false)?
+            .finish();
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+//! Messages from the Fleet Telemetry `vehicle_data.proto`, `vehicle_alert.proto` and
+//! `vehicle_error.proto`, written out by hand so the build doesn't need `protoc`. They are the
+//! payload of a [StreamMessage](super::message::StreamMessage).
+//!
+//! Only the parts teslatte decodes are here. Unknown fields are skipped by prost.
+use std::collections::HashMap;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Timestamp {
+    #[prost(int64, tag = "1")]
+    pub seconds: i64,
+    #[prost(int32, tag = "2")]
+    pub nanos: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Payload {
+    #[prost(message, repeated, tag = "1")]
+    pub data: Vec<Datum>,
+    #[prost(message, optional, tag = "2")]
+    pub created_at: Option<Timestamp>,
+    #[prost(string, tag = "3")]
+    pub vin: String,
+    #[prost(bool, tag = "4")]
+    pub is_resend: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Datum {
+    /// The `Field` enum.
+    #[prost(int32, tag = "1")]
+    pub key: i32,
+    #[prost(message, optional, tag = "2")]
+    pub value: Option<Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Value {
+    #[prost(oneof = "Kind", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10")]
+    pub value: Option<Kind>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum Kind {
+    #[prost(string, tag = "1")]
+    StringValue(String),
+    #[prost(int32, tag = "2")]
+    IntValue(i32),
+    #[prost(int64, tag = "3")]
+    LongValue(i64),
+    #[prost(float, tag = "4")]
+    FloatValue(f32),
+    #[prost(double, tag = "5")]
+    DoubleValue(f64),
+    #[prost(bool, tag = "6")]
+    BooleanValue(bool),
+    #[prost(message, tag = "7")]
+    LocationValue(LocationValue),
+    /// The `ChargingState` enum.
+    #[prost(int32, tag = "8")]
+    ChargingValue(i32),
+    /// The `ShiftState` enum.
+    #[prost(int32, tag = "9")]
+    ShiftStateValue(i32),
+    #[prost(bool, tag = "10")]
+    Invalid(bool),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LocationValue {
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VehicleAlerts {
+    #[prost(message, repeated, tag = "1")]
+    pub alerts: Vec<VehicleAlert>,
+    #[prost(message, optional, tag = "2")]
+    pub created_at: Option<Timestamp>,
+    #[prost(string, tag = "3")]
+    pub vin: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VehicleAlert {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    /// The `Audience` enum.
+    #[prost(int32, repeated, tag = "2")]
+    pub audiences: Vec<i32>,
+    #[prost(message, optional, tag = "3")]
+    pub started_at: Option<Timestamp>,
+    #[prost(message, optional, tag = "4")]
+    pub ended_at: Option<Timestamp>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VehicleErrors {
+    #[prost(message, repeated, tag = "1")]
+    pub errors: Vec<VehicleError>,
+    #[prost(message, optional, tag = "2")]
+    pub created_at: Option<Timestamp>,
+    #[prost(string, tag = "3")]
+    pub vin: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VehicleError {
+    #[prost(message, optional, tag = "1")]
+    pub created_at: Option<Timestamp>,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(map = "string, string", tag = "3")]
+    pub tags: HashMap<String, String>,
+    #[prost(string, tag = "4")]
+    pub body: String,
+}
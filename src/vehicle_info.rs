@@ -0,0 +1,351 @@
+//! Fleet API metadata about vehicles, mostly looked up by VIN.
+use crate::error::TeslatteError;
+use crate::{check_scope, pub_get_arg, OwnerApi, VehicleId};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[rustfmt::skip]
+impl OwnerApi {
+    pub_get_arg!(vehicle_specs, VehicleSpecs, "/vehicles/{}/specs", str, [VehicleDeviceData]);
+    pub_get_arg!(vehicle_options, VehicleOptions, "/dx/vehicles/options?vin={}", str, [VehicleDeviceData]);
+    pub_get_arg!(eligible_upgrades, EligibleUpgrades, "/dx/vehicles/upgrades/eligibility?vin={}", str, [VehicleDeviceData]);
+    pub_get_arg!(eligible_subscriptions, EligibleSubscriptions, "/dx/vehicles/subscriptions/eligibility?vin={}", str, [VehicleDeviceData]);
+    pub_get_arg!(warranty_details, WarrantyDetails, "/dx/warranty/details?vin={}", str, [VehicleDeviceData]);
+    pub_get_arg!(mobile_enabled, bool, "/vehicles/{}/mobile_enabled", VehicleId, [VehicleDeviceData]);
+    pub_get_arg!(recent_alerts, RecentAlerts, "/vehicles/{}/recent_alerts", VehicleId, [VehicleDeviceData]);
+}
+
+impl OwnerApi {
+    /// Whether the vehicles are paired with the application's key, and need signed commands.
+    pub async fn fleet_status(&self, vins: &[String]) -> Result<FleetStatus, TeslatteError> {
+        check_scope!(self, fleet_status, [VehicleDeviceData])?;
+        let url = format!("{}/vehicles/fleet_status", self.api_url);
+        self.post_data(&url, &FleetStatusRequest { vins }).await
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FleetStatusRequest<'a> {
+    vins: &'a [String],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VehicleSpecs {
+    pub vin: Option<String>,
+    pub model: Option<String>,
+    pub model_year: Option<i64>,
+    pub trim: Option<String>,
+    pub drive: Option<String>,
+    pub battery: Option<String>,
+    pub paint: Option<String>,
+    pub wheels: Option<String>,
+    pub interior: Option<String>,
+    #[serde(default)]
+    pub option_codes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VehicleOptions {
+    pub codes: Vec<OptionCode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionCode {
+    /// e.g. `$MT315`.
+    pub code: String,
+    pub display_name: Option<String>,
+    pub is_active: bool,
+    pub color_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetStatus {
+    /// Vehicles that have the application's virtual key.
+    pub key_paired_vins: Vec<String>,
+    pub unpaired_vins: Vec<String>,
+    /// By VIN.
+    #[serde(default)]
+    pub vehicle_info: HashMap<String, FleetStatusVehicleInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetStatusVehicleInfo {
+    pub firmware_version: Option<String>,
+    /// Commands have to be signed with the Vehicle Command Protocol, instead of sent to the
+    /// REST command endpoints.
+    pub vehicle_command_protocol_required: Option<bool>,
+    pub discounted_device_data: Option<bool>,
+    pub fleet_telemetry_version: Option<String>,
+    pub total_number_of_keys: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EligibleUpgrades {
+    pub vin: String,
+    pub country: Option<String>,
+    #[serde(rename = "type")]
+    pub vehicle_type: Option<String>,
+    pub eligible: Vec<EligibleUpgrade>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EligibleUpgrade {
+    pub option_code: String,
+    pub option_group: Option<String>,
+    pub current_option_code: Option<String>,
+    #[serde(default)]
+    pub pricing: Vec<Pricing>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pricing {
+    pub price: f64,
+    pub total: Option<f64>,
+    pub currency_code: String,
+    pub is_primary: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EligibleSubscriptions {
+    pub vin: String,
+    pub country: Option<String>,
+    pub eligible: Vec<EligibleSubscription>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EligibleSubscription {
+    pub option_code: String,
+    /// e.g. `Premium Connectivity`.
+    pub product: Option<String>,
+    pub start_date: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    pub addons: Vec<SubscriptionAddon>,
+    #[serde(default)]
+    pub billing_options: Vec<BillingOption>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionAddon {
+    pub billing_period: Option<String>,
+    pub currency_code: Option<String>,
+    pub option_code: String,
+    pub price: Option<f64>,
+    pub tax: Option<f64>,
+    pub total: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillingOption {
+    /// e.g. `monthly` or `yearly`.
+    pub billing_period: Option<String>,
+    pub currency_code: Option<String>,
+    pub option_code: Option<String>,
+    pub price: Option<f64>,
+    pub tax: Option<f64>,
+    pub total: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarrantyDetails {
+    #[serde(default)]
+    pub active_warranty: Vec<Warranty>,
+    #[serde(default)]
+    pub upcoming_warranty: Vec<Warranty>,
+    #[serde(default)]
+    pub expired_warranty: Vec<Warranty>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Warranty {
+    /// e.g. `NEW_MFG_BASIC_4_50ML`.
+    pub warranty_type: String,
+    pub warranty_display_name: Option<String>,
+    pub expiration_date: Option<DateTime<FixedOffset>>,
+    pub expiration_odometer: Option<i64>,
+    /// e.g. `MI` or `KM`.
+    pub odometer_unit: Option<String>,
+    /// Either `DATE` or `ODOMETER`, once expired.
+    pub warranty_expired_on: Option<String>,
+    pub coverage_age_in_years: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentAlerts {
+    pub recent_alerts: Vec<RecentAlert>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentAlert {
+    pub name: String,
+    pub time: DateTime<FixedOffset>,
+    /// Who the alert is for, e.g. `customer` or `service-fix`.
+    #[serde(default)]
+    pub audience: Vec<String>,
+    pub user_text: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PrintResponses, RequestData};
+
+    fn parse<T>(s: &str) -> T
+    where
+        T: for<'de> Deserialize<'de> + std::fmt::Debug,
+    {
+        let request_data = RequestData::Get { url: "" };
+        OwnerApi::parse_json::<T>(&request_data, s.to_string(), PrintResponses::No).unwrap()
+    }
+
+    #[test]
+    fn json_fleet_status() {
+        let status: FleetStatus = parse(
+            r#"{
+              "response": {
+                "key_paired_vins": [],
+                "unpaired_vins": ["5YJ3000000NEXUS01"],
+                "vehicle_info": {
+                  "5YJ3000000NEXUS01": {
+                    "firmware_version": "2024.14.30",
+                    "vehicle_command_protocol_required": true,
+                    "discounted_device_data": false,
+                    "fleet_telemetry_version": "1.0.0",
+                    "total_number_of_keys": 5
+                  }
+                }
+              }
+            }"#,
+        );
+        assert_eq!(status.unpaired_vins, ["5YJ3000000NEXUS01"]);
+        let info = &status.vehicle_info["5YJ3000000NEXUS01"];
+        assert_eq!(info.vehicle_command_protocol_required, Some(true));
+    }
+
+    #[test]
+    fn json_vehicle_options() {
+        let options: VehicleOptions = parse(
+            r#"{
+              "response": {
+                "codes": [
+                  {
+                    "code": "$MT315",
+                    "displayName": "Long Range All-Wheel Drive",
+                    "isActive": true
+                  },
+                  {
+                    "code": "$PPSW",
+                    "colorCode": "PPSW",
+                    "displayName": "Pearl White Multi-Coat",
+                    "isActive": true
+                  }
+                ]
+              }
+            }"#,
+        );
+        assert_eq!(options.codes[1].color_code.as_deref(), Some("PPSW"));
+    }
+
+    #[test]
+    fn json_eligible_upgrades_and_subscriptions() {
+        let upgrades: EligibleUpgrades = parse(
+            r#"{
+              "response": {
+                "vin": "TEST00000000VIN01",
+                "country": "US",
+                "type": "VEHICLE",
+                "eligible": [
+                  {
+                    "optionCode": "$FM3U",
+                    "optionGroup": "PERF_FIRMWARE",
+                    "currentOptionCode": "$FM3B",
+                    "pricing": [
+                      { "price": 2000, "total": 2000, "currencyCode": "USD", "isPrimary": true }
+                    ]
+                  }
+                ]
+              }
+            }"#,
+        );
+        assert_eq!(upgrades.eligible[0].pricing[0].price, 2000.0);
+
+        let subscriptions: EligibleSubscriptions = parse(
+            r#"{
+              "response": {
+                "country": "US",
+                "eligible": [
+                  {
+                    "addons": [],
+                    "billingOptions": [
+                      {
+                        "billingPeriod": "monthly",
+                        "currencyCode": "USD",
+                        "optionCode": "$CPF1",
+                        "price": 9.99,
+                        "tax": 0,
+                        "total": 9.99
+                      }
+                    ],
+                    "optionCode": "$CPF1",
+                    "product": "Premium Connectivity",
+                    "startDate": "2024-01-01T00:00:00Z"
+                  }
+                ],
+                "vin": "TEST00000000VIN01"
+              }
+            }"#,
+        );
+        assert_eq!(
+            subscriptions.eligible[0].product.as_deref(),
+            Some("Premium Connectivity")
+        );
+    }
+
+    #[test]
+    fn json_warranty_and_alerts() {
+        let warranty: WarrantyDetails = parse(
+            r#"{
+              "response": {
+                "activeWarranty": [
+                  {
+                    "warrantyType": "NEW_MFG_BASIC_4_50ML",
+                    "warrantyDisplayName": "Basic Vehicle Limited Warranty",
+                    "expirationDate": "2025-10-21T00:00:00Z",
+                    "expirationOdometer": 50000,
+                    "odometerUnit": "MI",
+                    "warrantyExpiredOn": null,
+                    "coverageAgeInYears": 4
+                  }
+                ],
+                "upcomingWarranty": [],
+                "expiredWarranty": []
+              }
+            }"#,
+        );
+        assert_eq!(warranty.active_warranty[0].expiration_odometer, Some(50000));
+
+        let alerts: RecentAlerts = parse(
+            r#"{
+              "response": {
+                "recent_alerts": [
+                  {
+                    "name": "Name_Of_The_Alert",
+                    "time": "2021-03-19T22:01:15.101+00:00",
+                    "audience": ["service-fix", "customer"],
+                    "user_text": "additional description text"
+                  }
+                ]
+              }
+            }"#,
+        );
+        assert_eq!(alerts.recent_alerts[0].audience.len(), 2);
+    }
+}
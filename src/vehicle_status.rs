@@ -0,0 +1,362 @@
+//! What a vehicle is doing, and events when that changes, from successive snapshots.
+//!
+//! ```no_run
+//! # use futures_util::StreamExt;
+//! # use std::sync::Arc;
+//! # use teslatte::monitor::{MonitorConfig, VehicleMonitor};
+//! # async fn run(api: teslatte::OwnerApi, vehicle_id: teslatte::VehicleId) {
+//! let monitor = VehicleMonitor::spawn(Arc::new(api), vehicle_id, MonitorConfig::default());
+//! let mut events = Box::pin(monitor.events());
+//! while let Some(event) = events.next().await {
+//!     println!("{event:?}");
+//! }
+//! # }
+//! ```
+use crate::error::TeslatteError;
+use crate::monitor::{VehicleMonitor, VehicleSnapshot};
+use crate::vehicles::{OnlineState, SoftwareUpdateStatus, VehicleData};
+use futures_util::{stream, Stream, StreamExt};
+use strum::Display;
+
+/// `VehicleState::center_display_state` while the display shows the Sentry Mode warning, after
+/// Sentry Mode detects something.
+const SENTRY_ALERT_DISPLAY_STATE: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum VehicleStatus {
+    Asleep,
+    /// Awake, but not driving, charging or updating.
+    Online,
+    Driving,
+    Charging,
+    /// Installing a software update.
+    Updating,
+    Offline,
+}
+
+impl VehicleStatus {
+    /// Without `data`, an online vehicle is assumed to be [VehicleStatus::Online].
+    pub fn of(state: OnlineState, data: Option<&VehicleData>) -> Self {
+        match (state, data) {
+            (OnlineState::Asleep, _) => VehicleStatus::Asleep,
+            (OnlineState::Offline | OnlineState::Unknown, _) => VehicleStatus::Offline,
+            (OnlineState::Online, None) => VehicleStatus::Online,
+            (OnlineState::Online, Some(data)) if data.is_driving() => VehicleStatus::Driving,
+            (OnlineState::Online, Some(data)) if data.is_charging() => VehicleStatus::Charging,
+            (OnlineState::Online, Some(data)) if data.is_updating() => VehicleStatus::Updating,
+            (OnlineState::Online, Some(_)) => VehicleStatus::Online,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VehicleEvent {
+    StatusChanged {
+        from: VehicleStatus,
+        to: VehicleStatus,
+    },
+    DriveStarted,
+    DriveEnded,
+    ChargingStarted,
+    /// Charging reached the charge limit.
+    ChargingCompleted,
+    /// Charging stopped before the charge limit, e.g. it was stopped or unplugged.
+    ChargingStopped,
+    PluggedIn,
+    Unplugged,
+    Locked,
+    Unlocked,
+    SentryTriggered,
+    SoftwareUpdateAvailable {
+        version: String,
+    },
+}
+
+/// What the previous vehicle data said, for comparing with the next.
+#[derive(Debug, Clone, PartialEq)]
+struct Facts {
+    driving: bool,
+    charging: bool,
+    charging_state: Option<String>,
+    locked: Option<bool>,
+    sentry_alert: bool,
+    update_version: Option<String>,
+}
+
+impl Facts {
+    fn of(data: &VehicleData) -> Self {
+        let vehicle_state = data.vehicle_state.as_ref();
+        let update = vehicle_state.map(|v| &v.software_update);
+        let update_available = update
+            .is_some_and(|u| u.is_pending() || u.status() == Some(SoftwareUpdateStatus::Available));
+        Facts {
+            driving: data.is_driving(),
+            charging: data.is_charging(),
+            charging_state: data.charge_state.as_ref().map(|c| c.charging_state.clone()),
+            locked: vehicle_state.map(|v| v.locked),
+            sentry_alert: vehicle_state.is_some_and(|v| {
+                v.sentry_mode == Some(true) && v.center_display_state == SENTRY_ALERT_DISPLAY_STATE
+            }),
+            update_version: update
+                .filter(|_| update_available)
+                .map(|u| u.version.trim().to_string()),
+        }
+    }
+}
+
+/// Turns snapshots into [VehicleEvent]s.
+///
+/// The first snapshot only sets the starting point, so it has no events. Snapshots without
+/// vehicle data only change the [VehicleStatus], so e.g. a drive doesn't end when the vehicle
+/// loses its connection.
+#[derive(Debug, Clone, Default)]
+pub struct VehicleTracker {
+    status: Option<VehicleStatus>,
+    facts: Option<Facts>,
+}
+
+impl VehicleTracker {
+    pub fn status(&self) -> Option<VehicleStatus> {
+        self.status
+    }
+
+    pub fn update(&mut self, snapshot: &VehicleSnapshot) -> Vec<VehicleEvent> {
+        let mut events = vec![];
+
+        let status = VehicleStatus::of(snapshot.state, snapshot.data.as_ref());
+        if let Some(from) = self.status.replace(status) {
+            if from != status {
+                events.push(VehicleEvent::StatusChanged { from, to: status });
+            }
+        }
+
+        if let Some(data) = &snapshot.data {
+            let facts = Facts::of(data);
+            if let Some(previous) = self.facts.replace(facts.clone()) {
+                facts.compare(&previous, &mut events);
+            }
+        }
+
+        events
+    }
+}
+
+impl Facts {
+    fn compare(&self, previous: &Facts, events: &mut Vec<VehicleEvent>) {
+        match (previous.driving, self.driving) {
+            (false, true) => events.push(VehicleEvent::DriveStarted),
+            (true, false) => events.push(VehicleEvent::DriveEnded),
+            _ => {}
+        }
+
+        let unplugged = |facts: &Facts| facts.charging_state.as_deref() == Some("Disconnected");
+        if let (Some(_), Some(_)) = (&previous.charging_state, &self.charging_state) {
+            match (unplugged(previous), unplugged(self)) {
+                (true, false) => events.push(VehicleEvent::PluggedIn),
+                (false, true) => events.push(VehicleEvent::Unplugged),
+                _ => {}
+            }
+        }
+
+        match (previous.charging, self.charging) {
+            (false, true) => events.push(VehicleEvent::ChargingStarted),
+            (true, false) if self.charging_state.as_deref() == Some("Complete") => {
+                events.push(VehicleEvent::ChargingCompleted)
+            }
+            (true, false) => events.push(VehicleEvent::ChargingStopped),
+            _ => {}
+        }
+
+        match (previous.locked, self.locked) {
+            (Some(false), Some(true)) => events.push(VehicleEvent::Locked),
+            (Some(true), Some(false)) => events.push(VehicleEvent::Unlocked),
+            _ => {}
+        }
+
+        if !previous.sentry_alert && self.sentry_alert {
+            events.push(VehicleEvent::SentryTriggered);
+        }
+
+        if let Some(version) = &self.update_version {
+            if previous.update_version.as_ref() != Some(version) {
+                events.push(VehicleEvent::SoftwareUpdateAvailable {
+                    version: version.clone(),
+                });
+            }
+        }
+    }
+}
+
+impl VehicleMonitor {
+    /// The [VehicleEvent]s of the snapshots, using a [VehicleTracker].
+    pub fn events(self) -> impl Stream<Item = Result<VehicleEvent, TeslatteError>> {
+        self.scan(VehicleTracker::default(), |tracker, snapshot| {
+            let events = match snapshot {
+                Ok(snapshot) => tracker.update(&snapshot).into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            futures_util::future::ready(Some(stream::iter(events)))
+        })
+        .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OwnerApi, PrintResponses, RequestData};
+    use chrono::Utc;
+
+    fn parse(s: &str) -> VehicleData {
+        let request_data = RequestData::Get { url: "" };
+        OwnerApi::parse_json::<VehicleData>(&request_data, s.to_string(), PrintResponses::No)
+            .unwrap()
+    }
+
+    /// Parked, unplugged and locked.
+    fn parked() -> VehicleData {
+        parse(include_str!(
+            "../testdata/vehicle_data_HTLC_2023_10_19.json"
+        ))
+    }
+
+    fn snapshot(state: OnlineState, data: Option<VehicleData>) -> VehicleSnapshot {
+        VehicleSnapshot {
+            time: Utc::now(),
+            state,
+            data,
+        }
+    }
+
+    fn online(data: VehicleData) -> VehicleSnapshot {
+        snapshot(OnlineState::Online, Some(data))
+    }
+
+    fn with(mut data: VehicleData, f: impl FnOnce(&mut VehicleData)) -> VehicleData {
+        f(&mut data);
+        data
+    }
+
+    fn shift(data: &mut VehicleData, shift_state: &str) {
+        data.drive_state.as_mut().unwrap().shift_state = Some(shift_state.to_string());
+    }
+
+    fn charging_state(data: &mut VehicleData, charging_state: &str) {
+        data.charge_state.as_mut().unwrap().charging_state = charging_state.to_string();
+    }
+
+    #[test]
+    fn status() {
+        assert_eq!(
+            VehicleStatus::of(OnlineState::Asleep, None),
+            VehicleStatus::Asleep
+        );
+        assert_eq!(
+            VehicleStatus::of(OnlineState::Online, Some(&parked())),
+            VehicleStatus::Online
+        );
+        let charging = with(parked(), |d| charging_state(d, "Charging"));
+        assert_eq!(
+            VehicleStatus::of(OnlineState::Online, Some(&charging)),
+            VehicleStatus::Charging
+        );
+        let updating = with(parked(), |d| {
+            d.vehicle_state.as_mut().unwrap().software_update.status = "installing".to_string()
+        });
+        assert_eq!(
+            VehicleStatus::of(OnlineState::Online, Some(&updating)),
+            VehicleStatus::Updating
+        );
+    }
+
+    #[test]
+    fn drive() {
+        let mut tracker = VehicleTracker::default();
+        assert!(tracker
+            .update(&snapshot(OnlineState::Asleep, None))
+            .is_empty());
+        assert_eq!(
+            tracker.update(&online(parked())),
+            [VehicleEvent::StatusChanged {
+                from: VehicleStatus::Asleep,
+                to: VehicleStatus::Online
+            }]
+        );
+
+        let driving = with(parked(), |d| {
+            shift(d, "D");
+            d.vehicle_state.as_mut().unwrap().locked = false;
+        });
+        assert_eq!(
+            tracker.update(&online(driving.clone())),
+            [
+                VehicleEvent::StatusChanged {
+                    from: VehicleStatus::Online,
+                    to: VehicleStatus::Driving
+                },
+                VehicleEvent::DriveStarted,
+                VehicleEvent::Unlocked,
+            ]
+        );
+        assert!(tracker.update(&online(driving)).is_empty());
+
+        // Losing the connection doesn't end the drive.
+        tracker.update(&snapshot(OnlineState::Offline, None));
+        assert_eq!(tracker.status(), Some(VehicleStatus::Offline));
+
+        let parked = with(parked(), |d| shift(d, "P"));
+        let events = tracker.update(&online(parked));
+        assert!(events.contains(&VehicleEvent::DriveEnded));
+        assert!(events.contains(&VehicleEvent::Locked));
+    }
+
+    #[test]
+    fn charging() {
+        let mut tracker = VehicleTracker::default();
+        tracker.update(&online(parked()));
+
+        let events = tracker.update(&online(with(parked(), |d| charging_state(d, "Stopped"))));
+        assert_eq!(events, [VehicleEvent::PluggedIn]);
+
+        let events = tracker.update(&online(with(parked(), |d| charging_state(d, "Charging"))));
+        assert!(events.contains(&VehicleEvent::ChargingStarted));
+
+        let events = tracker.update(&online(with(parked(), |d| charging_state(d, "Complete"))));
+        assert!(events.contains(&VehicleEvent::ChargingCompleted));
+
+        tracker.update(&online(with(parked(), |d| charging_state(d, "Charging"))));
+        let events = tracker.update(&online(parked()));
+        assert!(events.contains(&VehicleEvent::ChargingStopped));
+        assert!(events.contains(&VehicleEvent::Unplugged));
+    }
+
+    #[test]
+    fn sentry_and_software_update() {
+        // Sentry Mode is on in this one.
+        let sentry = parse(include_str!("../testdata/vehicle_data_gak_2023_11_11.json"));
+        let mut tracker = VehicleTracker::default();
+        tracker.update(&online(sentry.clone()));
+
+        let alert = with(sentry.clone(), |d| {
+            d.vehicle_state.as_mut().unwrap().center_display_state = SENTRY_ALERT_DISPLAY_STATE
+        });
+        assert_eq!(
+            tracker.update(&online(alert)),
+            [VehicleEvent::SentryTriggered]
+        );
+
+        let available = with(sentry, |d| {
+            let update = &mut d.vehicle_state.as_mut().unwrap().software_update;
+            update.status = "available".to_string();
+            update.version = "2023.44.30".to_string();
+        });
+        assert_eq!(
+            tracker.update(&online(available.clone())),
+            [VehicleEvent::SoftwareUpdateAvailable {
+                version: "2023.44.30".to_string()
+            }]
+        );
+        assert!(tracker.update(&online(available)).is_empty());
+    }
+}
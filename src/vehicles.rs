@@ -3,43 +3,308 @@ use derive_more::{Deref, DerefMut, From};
 //
 // Sometimes the API will return a null for a field where I've put in a non Option type, which
 // will cause the deserializer to fail. Please log an issue to fix these if you come across it.
+use crate::error::TeslatteError;
+use crate::products::Product;
+use crate::redact::redacted_fmt;
+use crate::scopes::Scope;
 use crate::{
-    get_args, post_arg, post_arg_empty, ApiValues, Empty, ExternalVehicleId, OwnerApi, VehicleApi,
-    VehicleId,
+    get_arg, get_args, join_query_pairs, post_arg, post_arg_empty, ApiValues, Empty,
+    ExternalVehicleId, OwnerApi, VehicleApi, VehicleId,
 };
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use strum::{Display, EnumString};
 
 #[rustfmt::skip]
 impl VehicleApi for OwnerApi {
-    get_args!(vehicle_data, VehicleData, "/vehicles/{}/vehicle_data", GetVehicleData);
-    post_arg_empty!(wake_up, "/vehicles/{}/command/wake_up", VehicleId);
+    get_arg!(vehicle, Vehicle, "/vehicles/{}", VehicleId, [VehicleDeviceData]);
+    get_args!(vehicle_data, VehicleData, "/vehicles/{}/vehicle_data", GetVehicleData, [VehicleDeviceData], Self::prepare_vehicle_data);
+    get_args!(nearby_charging_sites, NearbyChargingSites, "/vehicles/{}/nearby_charging_sites", NearbyChargingSitesValues, [VehicleDeviceData]);
+    post_arg_empty!(wake_up, "/vehicles/{}/command/wake_up", VehicleId, [VehicleDeviceData]);
 
     // Alerts
-    post_arg_empty!(honk_horn, "/vehicles/{}/command/honk_horn", VehicleId);
-    post_arg_empty!(flash_lights, "/vehicles/{}/command/flash_lights", VehicleId);
+    post_arg_empty!(honk_horn, "/vehicles/{}/command/honk_horn", VehicleId, [VehicleCmds]);
+    post_arg_empty!(flash_lights, "/vehicles/{}/command/flash_lights", VehicleId, [VehicleCmds]);
 
     // Charging
-    post_arg_empty!(charge_port_door_open, "/vehicles/{}/command/charge_port_door_open", VehicleId);
-    post_arg_empty!(charge_port_door_close, "/vehicles/{}/command/charge_port_door_close", VehicleId);
-    post_arg!(set_charge_limit, SetChargeLimit, "/vehicles/{}/command/set_charge_limit", VehicleId);
-    post_arg!(set_charging_amps, SetChargingAmps, "/vehicles/{}/command/set_charging_amps", VehicleId);
-    post_arg_empty!(charge_standard, "/vehicles/{}/command/charge_standard", VehicleId);
-    post_arg_empty!(charge_max_range, "/vehicles/{}/command/charge_max_range", VehicleId);
-    post_arg_empty!(charge_start, "/vehicles/{}/command/charge_start", VehicleId);
-    post_arg_empty!(charge_stop, "/vehicles/{}/command/charge_stop", VehicleId);
-    post_arg!(set_scheduled_charging, SetScheduledCharging, "/vehicles/{}/command/set_scheduled_charging", VehicleId);
-    post_arg!(set_scheduled_departure, SetScheduledDeparture, "/vehicles/{}/command/set_scheduled_departure", VehicleId);
+    post_arg_empty!(charge_port_door_open, "/vehicles/{}/command/charge_port_door_open", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg_empty!(charge_port_door_close, "/vehicles/{}/command/charge_port_door_close", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg!(set_charge_limit, SetChargeLimit, "/vehicles/{}/command/set_charge_limit", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg!(set_charging_amps, SetChargingAmps, "/vehicles/{}/command/set_charging_amps", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg_empty!(charge_standard, "/vehicles/{}/command/charge_standard", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg_empty!(charge_max_range, "/vehicles/{}/command/charge_max_range", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg_empty!(charge_start, "/vehicles/{}/command/charge_start", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg_empty!(charge_stop, "/vehicles/{}/command/charge_stop", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg!(set_scheduled_charging, SetScheduledCharging, "/vehicles/{}/command/set_scheduled_charging", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg!(set_scheduled_departure, SetScheduledDeparture, "/vehicles/{}/command/set_scheduled_departure", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg!(add_charge_schedule, AddChargeSchedule, "/vehicles/{}/command/add_charge_schedule", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg!(remove_charge_schedule, RemoveSchedule, "/vehicles/{}/command/remove_charge_schedule", VehicleId, [VehicleChargingCmds, VehicleCmds]);
+    post_arg!(add_precondition_schedule, AddPreconditionSchedule, "/vehicles/{}/command/add_precondition_schedule", VehicleId, [VehicleCmds]);
+    post_arg!(remove_precondition_schedule, RemoveSchedule, "/vehicles/{}/command/remove_precondition_schedule", VehicleId, [VehicleCmds]);
 
     // HVAC
-    post_arg_empty!(auto_conditioning_start, "/vehicles/{}/command/auto_conditioning_start", VehicleId);
-    post_arg_empty!(auto_conditioning_stop, "/vehicles/{}/command/auto_conditioning_stop", VehicleId);
-    post_arg!(set_temps, SetTemperatures, "/vehicles/{}/command/set_temps", VehicleId);
+    post_arg_empty!(auto_conditioning_start, "/vehicles/{}/command/auto_conditioning_start", VehicleId, [VehicleCmds]);
+    post_arg_empty!(auto_conditioning_stop, "/vehicles/{}/command/auto_conditioning_stop", VehicleId, [VehicleCmds]);
+    post_arg!(set_temps, SetTemperatures, "/vehicles/{}/command/set_temps", VehicleId, [VehicleCmds]);
+    post_arg!(remote_seat_heater_request, RemoteSeatHeater, "/vehicles/{}/command/remote_seat_heater_request", VehicleId, [VehicleCmds], Self::check_seat_heater);
+    post_arg!(remote_seat_cooler_request, RemoteSeatCooler, "/vehicles/{}/command/remote_seat_cooler_request", VehicleId, [VehicleCmds], Self::check_seat_cooler);
+    post_arg!(remote_auto_seat_climate_request, RemoteAutoSeatClimate, "/vehicles/{}/command/remote_auto_seat_climate_request", VehicleId, [VehicleCmds]);
+    post_arg!(remote_steering_wheel_heater_request, RemoteSteeringWheelHeater, "/vehicles/{}/command/remote_steering_wheel_heater_request", VehicleId, [VehicleCmds]);
+    post_arg!(remote_steering_wheel_heat_level_request, RemoteSteeringWheelHeatLevel, "/vehicles/{}/command/remote_steering_wheel_heat_level_request", VehicleId, [VehicleCmds]);
+    post_arg!(remote_auto_steering_wheel_heat_climate_request, RemoteAutoSteeringWheelHeat, "/vehicles/{}/command/remote_auto_steering_wheel_heat_climate_request", VehicleId, [VehicleCmds]);
+    post_arg!(set_climate_keeper_mode, SetClimateKeeperMode, "/vehicles/{}/command/set_climate_keeper_mode", VehicleId, [VehicleCmds]);
+    post_arg!(set_cabin_overheat_protection, SetCabinOverheatProtection, "/vehicles/{}/command/set_cabin_overheat_protection", VehicleId, [VehicleCmds]);
+    post_arg!(set_cop_temp, SetCopTemp, "/vehicles/{}/command/set_cop_temp", VehicleId, [VehicleCmds]);
+    post_arg!(set_bioweapon_mode, SetBioweaponMode, "/vehicles/{}/command/set_bioweapon_mode", VehicleId, [VehicleCmds]);
+    post_arg!(set_preconditioning_max, SetPreconditioningMax, "/vehicles/{}/command/set_preconditioning_max", VehicleId, [VehicleCmds]);
 
     // Doors
-    post_arg_empty!(door_unlock, "/vehicles/{}/command/door_unlock", VehicleId);
-    post_arg_empty!(door_lock, "/vehicles/{}/command/door_lock", VehicleId);
-    post_arg_empty!(remote_start_drive, "/vehicles/{}/command/remote_start_drive", VehicleId);
+    post_arg_empty!(door_unlock, "/vehicles/{}/command/door_unlock", VehicleId, [VehicleCmds]);
+    post_arg_empty!(door_lock, "/vehicles/{}/command/door_lock", VehicleId, [VehicleCmds]);
+    post_arg_empty!(remote_start_drive, "/vehicles/{}/command/remote_start_drive", VehicleId, [VehicleCmds]);
+    post_arg!(actuate_trunk, ActuateTrunk, "/vehicles/{}/command/actuate_trunk", VehicleId, [VehicleCmds]);
+
+    // Windows
+    post_arg!(window_control, WindowControl, "/vehicles/{}/command/window_control", VehicleId, [VehicleCmds], Self::prepare_window_control);
+    post_arg!(sun_roof_control, SunRoofControl, "/vehicles/{}/command/sun_roof_control", VehicleId, [VehicleCmds]);
+
+    // Security
+    post_arg!(set_sentry_mode, SetSentryMode, "/vehicles/{}/command/set_sentry_mode", VehicleId, [VehicleCmds]);
+    post_arg!(set_valet_mode, SetValetMode, "/vehicles/{}/command/set_valet_mode", VehicleId, [VehicleCmds]);
+    post_arg_empty!(reset_valet_pin, "/vehicles/{}/command/reset_valet_pin", VehicleId, [VehicleCmds]);
+    post_arg!(set_pin_to_drive, SetPinToDrive, "/vehicles/{}/command/set_pin_to_drive", VehicleId, [VehicleCmds]);
+    post_arg_empty!(reset_pin_to_drive_pin, "/vehicles/{}/command/reset_pin_to_drive_pin", VehicleId, [VehicleCmds]);
+
+    // Speed limit
+    post_arg!(speed_limit_activate, SpeedLimitPin, "/vehicles/{}/command/speed_limit_activate", VehicleId, [VehicleCmds]);
+    post_arg!(speed_limit_deactivate, SpeedLimitPin, "/vehicles/{}/command/speed_limit_deactivate", VehicleId, [VehicleCmds]);
+    post_arg!(speed_limit_set_limit, SpeedLimitSetLimit, "/vehicles/{}/command/speed_limit_set_limit", VehicleId, [VehicleCmds]);
+    post_arg!(speed_limit_clear_pin, SpeedLimitPin, "/vehicles/{}/command/speed_limit_clear_pin", VehicleId, [VehicleCmds]);
+
+    // Media
+    post_arg!(adjust_volume, AdjustVolume, "/vehicles/{}/command/adjust_volume", VehicleId, [VehicleCmds]);
+    post_arg_empty!(media_toggle_playback, "/vehicles/{}/command/media_toggle_playback", VehicleId, [VehicleCmds]);
+    post_arg_empty!(media_next_track, "/vehicles/{}/command/media_next_track", VehicleId, [VehicleCmds]);
+    post_arg_empty!(media_prev_track, "/vehicles/{}/command/media_prev_track", VehicleId, [VehicleCmds]);
+    post_arg_empty!(media_next_fav, "/vehicles/{}/command/media_next_fav", VehicleId, [VehicleCmds]);
+    post_arg_empty!(media_prev_fav, "/vehicles/{}/command/media_prev_fav", VehicleId, [VehicleCmds]);
+    post_arg_empty!(media_volume_up, "/vehicles/{}/command/media_volume_up", VehicleId, [VehicleCmds]);
+    post_arg_empty!(media_volume_down, "/vehicles/{}/command/media_volume_down", VehicleId, [VehicleCmds]);
+
+    // Navigation
+    post_arg!(navigation_request, NavigationRequest, "/vehicles/{}/command/navigation_request", VehicleId, [VehicleCmds], Self::check_navigation);
+    post_arg!(navigation_gps_request, NavigationGpsRequest, "/vehicles/{}/command/navigation_gps_request", VehicleId, [VehicleCmds], Self::check_navigation);
+    post_arg!(navigation_sc_request, NavigationScRequest, "/vehicles/{}/command/navigation_sc_request", VehicleId, [VehicleCmds], Self::check_navigation);
+    post_arg!(navigation_waypoints_request, NavigationWaypointsRequest, "/vehicles/{}/command/navigation_waypoints_request", VehicleId, [VehicleCmds], Self::check_navigation);
+
+    // Software update
+    post_arg!(schedule_software_update, ScheduleSoftwareUpdate, "/vehicles/{}/command/schedule_software_update", VehicleId, [VehicleCmds]);
+    post_arg_empty!(cancel_software_update, "/vehicles/{}/command/cancel_software_update", VehicleId, [VehicleCmds]);
+
+    // Misc
+    post_arg!(trigger_homelink, TriggerHomelink, "/vehicles/{}/command/trigger_homelink", VehicleId, [VehicleCmds], Self::prepare_trigger_homelink);
+    post_arg!(remote_boombox, RemoteBoombox, "/vehicles/{}/command/remote_boombox", VehicleId, [VehicleCmds]);
+    post_arg!(set_vehicle_name, SetVehicleName, "/vehicles/{}/command/set_vehicle_name", VehicleId, [VehicleCmds]);
+    post_arg_empty!(erase_user_data, "/vehicles/{}/command/erase_user_data", VehicleId, [VehicleCmds]);
+}
+
+impl OwnerApi {
+    /// Location data also needs [Scope::VehicleLocation]. When [GetVehicleData::require_online]
+    /// is set, the vehicle summary is checked first, so a sleeping vehicle isn't kept awake.
+    async fn prepare_vehicle_data(
+        &self,
+        get_vehicle_data: &GetVehicleData,
+    ) -> Result<(), TeslatteError> {
+        if get_vehicle_data
+            .endpoints
+            .iter()
+            .any(|e| matches!(e, Endpoint::LocationData))
+        {
+            self.check_scope("vehicle_data", &[Scope::VehicleLocation])?;
+        }
+
+        if get_vehicle_data.require_online {
+            let vehicle = self.vehicle(&get_vehicle_data.vehicle_id).await?;
+            if vehicle.state != OnlineState::Online {
+                return Err(TeslatteError::VehicleNotOnline {
+                    vehicle_id: vehicle.id,
+                    state: vehicle.state,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// [VehicleConfig] of the vehicle, for commands that depend on what it has.
+    async fn vehicle_config(&self, vehicle_id: &VehicleId) -> Result<VehicleConfig, TeslatteError> {
+        let get_vehicle_data =
+            GetVehicleData::new_with_endpoints(vehicle_id.clone(), vec![Endpoint::VehicleConfig]);
+        self.vehicle_data(&get_vehicle_data)
+            .await?
+            .vehicle_config
+            .ok_or_else(|| TeslatteError::VehicleConfigUnknown {
+                vehicle_id: vehicle_id.clone(),
+            })
+    }
+
+    async fn check_seat_heater<'a>(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &'a RemoteSeatHeater,
+    ) -> Result<&'a RemoteSeatHeater, TeslatteError> {
+        data.heater
+            .check_heater(&self.vehicle_config(vehicle_id).await?)?;
+        Ok(data)
+    }
+
+    async fn check_seat_cooler<'a>(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &'a RemoteSeatCooler,
+    ) -> Result<&'a RemoteSeatCooler, TeslatteError> {
+        data.seat_position
+            .check_cooler(&self.vehicle_config(vehicle_id).await?)?;
+        Ok(data)
+    }
+
+    async fn check_navigation<'a, T>(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &'a T,
+    ) -> Result<&'a T, TeslatteError> {
+        self.vehicle_config(vehicle_id).await?.check_navigation()?;
+        Ok(data)
+    }
+
+    /// Closing the windows needs the location of the vehicle.
+    async fn prepare_window_control(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &WindowControl,
+    ) -> Result<WindowControl, TeslatteError> {
+        let mut data = data.clone();
+        if data.command == WindowCommand::Close && (data.lat.is_none() || data.lon.is_none()) {
+            let (lat, lon) = self.vehicle_location(vehicle_id).await?;
+            data.lat = Some(lat);
+            data.lon = Some(lon);
+        }
+        Ok(data)
+    }
+
+    async fn prepare_trigger_homelink(
+        &self,
+        vehicle_id: &VehicleId,
+        data: &TriggerHomelink,
+    ) -> Result<TriggerHomelink, TeslatteError> {
+        // Location data needs its own scope, so only ask for it when it is needed.
+        let needs_location = data.lat.is_none() || data.lon.is_none();
+        let mut endpoints = vec![Endpoint::VehicleState];
+        if needs_location {
+            endpoints.extend([Endpoint::DriveState, Endpoint::LocationData]);
+        }
+        let get_vehicle_data = GetVehicleData::new_with_endpoints(vehicle_id.clone(), endpoints);
+        let vehicle_data = self.vehicle_data(&get_vehicle_data).await?;
+        let homelink_nearby = vehicle_data.vehicle_state.and_then(|v| v.homelink_nearby);
+        if homelink_nearby != Some(true) {
+            return Err(TeslatteError::HomelinkNotNearby {
+                vehicle_id: vehicle_id.clone(),
+            });
+        }
+
+        let mut data = data.clone();
+        if needs_location {
+            let drive_state = vehicle_data.drive_state;
+            let Some((lat, lon)) = drive_state.and_then(|d| d.latitude.zip(d.longitude)) else {
+                return Err(TeslatteError::VehicleLocationUnknown {
+                    vehicle_id: vehicle_id.clone(),
+                });
+            };
+            data.lat = Some(lat);
+            data.lon = Some(lon);
+        }
+        Ok(data)
+    }
+
+    /// Latitude and longitude of the vehicle from [DriveState], for commands that need them.
+    async fn vehicle_location(&self, vehicle_id: &VehicleId) -> Result<(f64, f64), TeslatteError> {
+        let get_vehicle_data = GetVehicleData::new_with_endpoints(
+            vehicle_id.clone(),
+            vec![Endpoint::DriveState, Endpoint::LocationData],
+        );
+        let drive_state = self.vehicle_data(&get_vehicle_data).await?.drive_state;
+        match drive_state.and_then(|d| d.latitude.zip(d.longitude)) {
+            Some(location) => Ok(location),
+            None => Err(TeslatteError::VehicleLocationUnknown {
+                vehicle_id: vehicle_id.clone(),
+            }),
+        }
+    }
+
+    /// List of vehicles on the account.
+    ///
+    /// The Owner API `/vehicles` endpoint is gone, so this is taken from [OwnerApi::products()].
+    pub async fn vehicles(&self) -> Result<Vec<Vehicle>, TeslatteError> {
+        let products = self.products().await?;
+        Ok(vehicles_from_products(&products))
+    }
+
+    /// Poll [SoftwareUpdate] every `interval` until the update is no longer scheduled,
+    /// downloading or installing, calling `on_progress` with each reading.
+    ///
+    /// The vehicle is never woken up. While it is asleep or offline, e.g. rebooting during the
+    /// install, it is polled again after the next interval. Polling an online vehicle keeps it
+    /// awake, so use a long interval when the update is scheduled hours ahead.
+    ///
+    /// Fails with [TeslatteError::SoftwareUpdateTimeout] when the update is still pending, or the
+    /// vehicle still offline, after `timeout`.
+    pub async fn wait_for_software_update(
+        &self,
+        vehicle_id: &VehicleId,
+        interval: std::time::Duration,
+        timeout: std::time::Duration,
+        mut on_progress: impl FnMut(&SoftwareUpdate),
+    ) -> Result<SoftwareUpdate, TeslatteError> {
+        let mut get_vehicle_data =
+            GetVehicleData::new_with_endpoints(vehicle_id.clone(), vec![Endpoint::VehicleState]);
+        get_vehicle_data.require_online = true;
+
+        let poll = async {
+            loop {
+                match self.vehicle_data(&get_vehicle_data).await {
+                    Ok(VehicleData {
+                        vehicle_state: Some(vehicle_state),
+                        ..
+                    }) => {
+                        let software_update = vehicle_state.software_update;
+                        on_progress(&software_update);
+                        if !software_update.is_pending() {
+                            return Ok(software_update);
+                        }
+                    }
+                    Ok(_) | Err(TeslatteError::VehicleNotOnline { .. }) => {}
+                    Err(e) => return Err(e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        };
+        tokio::time::timeout(timeout, poll).await.map_err(|_| {
+            TeslatteError::SoftwareUpdateTimeout {
+                vehicle_id: vehicle_id.clone(),
+                timeout,
+            }
+        })?
+    }
+}
+
+/// The vehicles in a list of products, skipping energy sites and Powerwalls.
+fn vehicles_from_products(products: &[Product]) -> Vec<Vehicle> {
+    products
+        .iter()
+        .filter_map(|product| match product {
+            Product::Vehicle(vehicle_data) => Some(Vehicle::from(vehicle_data.as_ref())),
+            _ => None,
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone, Display, EnumString)]
@@ -54,6 +319,8 @@ pub enum Endpoint {
     VehicleConfig,
     VehicleState,
     VehicleDataCombo,
+    ChargeScheduleData,
+    PreconditioningScheduleData,
 }
 
 #[derive(Debug, Clone, From, Deref, DerefMut)]
@@ -68,8 +335,16 @@ pub struct GetVehicleData {
     /// From https://developer.tesla.com/docs/fleet-api#vehicle_data
     /// String of URL-encoded, semicolon-separated values. Can be many of 'charge_state',
     /// 'climate_state', 'closures_state', 'drive_state', 'gui_settings', 'location_data',
-    /// 'vehicle_config', 'vehicle_state', 'vehicle_data_combo'.
+    /// 'vehicle_config', 'vehicle_state', 'vehicle_data_combo', 'charge_schedule_data',
+    /// 'preconditioning_schedule_data'.
     pub endpoints: Endpoints,
+
+    /// Fail with [TeslatteError::VehicleNotOnline] instead of fetching the data when the vehicle
+    /// is asleep or offline.
+    ///
+    /// Requesting vehicle data from a sleeping vehicle fails with a 408, and polling it stops the
+    /// vehicle from going to sleep.
+    pub require_online: bool,
 }
 
 impl GetVehicleData {
@@ -97,8 +372,20 @@ impl GetVehicleData {
         Self {
             vehicle_id: vehicle_id.into(),
             endpoints: endpoints.into(),
+            require_online: false,
         }
     }
+
+    /// Only fetch the data if the vehicle is already online. See [GetVehicleData::require_online].
+    ///
+    /// ```rust
+    /// # use teslatte::vehicles::GetVehicleData;
+    /// let get_vehicle_data = GetVehicleData::new(123u64).with_require_online();
+    /// ```
+    pub fn with_require_online(mut self) -> Self {
+        self.require_online = true;
+        self
+    }
 }
 
 impl ApiValues for GetVehicleData {
@@ -151,6 +438,8 @@ pub struct VehicleData {
     pub gui_settings: Option<GuiSettings>,
     pub vehicle_config: Option<VehicleConfig>,
     pub vehicle_state: Option<VehicleState>,
+    pub charge_schedule_data: Option<ChargeScheduleData>,
+    pub preconditioning_schedule_data: Option<PreconditioningScheduleData>,
 
     pub cached_data: Option<String>,
     pub command_signing: Option<String>,
@@ -159,6 +448,34 @@ pub struct VehicleData {
     pub mobile_access_disabled: Option<bool>,
 }
 
+impl VehicleData {
+    /// In drive, reverse or neutral.
+    pub fn is_driving(&self) -> bool {
+        let shift_state = self
+            .drive_state
+            .as_ref()
+            .and_then(|d| d.shift_state.as_deref());
+        matches!(shift_state, Some("D" | "R" | "N"))
+    }
+
+    pub fn is_charging(&self) -> bool {
+        let charging_state = self
+            .charge_state
+            .as_ref()
+            .map(|c| c.charging_state.as_str());
+        matches!(charging_state, Some("Starting" | "Charging"))
+    }
+
+    /// A software update is installing.
+    pub fn is_updating(&self) -> bool {
+        let status = self
+            .vehicle_state
+            .as_ref()
+            .and_then(|v| v.software_update.status());
+        status == Some(SoftwareUpdateStatus::Installing)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ChargeState {
     pub battery_heater_on: bool,
@@ -348,6 +665,18 @@ pub struct VehicleConfig {
     pub webcam_selfie_supported: Option<bool>,
 }
 
+impl VehicleConfig {
+    /// Check the vehicle accepts destinations, using
+    /// [VehicleConfig::can_accept_navigation_requests].
+    pub fn check_navigation(&self) -> Result<(), TeslatteError> {
+        if self.can_accept_navigation_requests {
+            Ok(())
+        } else {
+            Err(TeslatteError::NavigationNotSupported)
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VehicleState {
     pub api_version: i64,
@@ -431,9 +760,21 @@ pub struct MediaState {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaInfo {
-    audio_volume: Option<f64>,
-    audio_volume_increment: Option<f64>,
-    audio_volume_max: Option<f64>,
+    pub a2dp_source_name: Option<String>,
+    pub audio_volume: Option<f64>,
+    pub audio_volume_increment: Option<f64>,
+    pub audio_volume_max: Option<f64>,
+    /// e.g. "Playing", "Paused", "Stopped".
+    pub media_playback_status: Option<String>,
+    pub now_playing_album: Option<String>,
+    pub now_playing_artist: Option<String>,
+    /// Milliseconds.
+    pub now_playing_duration: Option<i64>,
+    /// Milliseconds.
+    pub now_playing_elapsed: Option<i64>,
+    pub now_playing_source: Option<String>,
+    pub now_playing_station: Option<String>,
+    pub now_playing_title: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -441,10 +782,46 @@ pub struct SoftwareUpdate {
     pub download_perc: i64,
     pub expected_duration_sec: i64,
     pub install_perc: i64,
+    /// See [SoftwareUpdateStatus].
     pub status: String,
     pub version: String,
 }
 
+impl SoftwareUpdate {
+    /// Unknown statuses are `None`.
+    pub fn status(&self) -> Option<SoftwareUpdateStatus> {
+        self.status.parse().ok()
+    }
+
+    /// Whether an update is scheduled, downloading or installing.
+    pub fn is_pending(&self) -> bool {
+        matches!(
+            self.status(),
+            Some(
+                SoftwareUpdateStatus::Scheduled
+                    | SoftwareUpdateStatus::Downloading
+                    | SoftwareUpdateStatus::DownloadingWifiWait
+                    | SoftwareUpdateStatus::Installing
+            )
+        )
+    }
+}
+
+/// Software update status, as reported in [SoftwareUpdate::status], e.g.
+/// `"downloading_wifi_wait".parse::<SoftwareUpdateStatus>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum SoftwareUpdateStatus {
+    /// Reported as an empty string.
+    #[strum(serialize = "")]
+    NoUpdate,
+    Available,
+    Scheduled,
+    Downloading,
+    DownloadingWifiWait,
+    Installing,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpeedLimitMode {
     pub active: bool,
@@ -459,12 +836,50 @@ pub struct GranularAccess {
     pub hide_private: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Vehicle {
     pub id: VehicleId,
     pub vehicle_id: ExternalVehicleId,
     pub vin: String,
-    pub display_name: String,
+    pub display_name: Option<String>,
+    pub state: OnlineState,
+    pub in_service: bool,
+}
+
+impl From<&VehicleData> for Vehicle {
+    fn from(vehicle_data: &VehicleData) -> Self {
+        Self {
+            id: vehicle_data.id.clone(),
+            vehicle_id: vehicle_data.vehicle_id.clone(),
+            vin: vehicle_data.vin.clone(),
+            display_name: vehicle_data.display_name.clone(),
+            state: OnlineState::from(vehicle_data.state.as_str()),
+            in_service: vehicle_data.in_service,
+        }
+    }
+}
+
+/// Whether the vehicle is connected, from the `state` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum OnlineState {
+    Online,
+    Asleep,
+    Offline,
+    #[serde(other)]
+    Unknown,
+}
+
+impl From<&str> for OnlineState {
+    fn from(s: &str) -> Self {
+        match s {
+            "online" => OnlineState::Online,
+            "asleep" => OnlineState::Asleep,
+            "offline" => OnlineState::Offline,
+            _ => OnlineState::Unknown,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -487,6 +902,316 @@ pub struct SetTemperatures {
     pub passenger_temp: f32,
 }
 
+/// A seat, for the seat heater and cooler commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Seat {
+    FrontLeft,
+    FrontRight,
+    RearLeft,
+    RearCenter,
+    RearRight,
+    ThirdRowLeft,
+    ThirdRowRight,
+}
+
+impl Seat {
+    /// The number the API uses for the seat.
+    pub fn id(&self) -> u8 {
+        match self {
+            Seat::FrontLeft => 0,
+            Seat::FrontRight => 1,
+            Seat::RearLeft => 2,
+            Seat::RearCenter => 4,
+            Seat::RearRight => 5,
+            Seat::ThirdRowLeft => 6,
+            Seat::ThirdRowRight => 7,
+        }
+    }
+
+    pub fn is_front(&self) -> bool {
+        matches!(self, Seat::FrontLeft | Seat::FrontRight)
+    }
+
+    pub fn is_third_row(&self) -> bool {
+        matches!(self, Seat::ThirdRowLeft | Seat::ThirdRowRight)
+    }
+
+    /// Check the vehicle has a heater in this seat, using [VehicleConfig::rear_seat_heaters] and
+    /// [VehicleConfig::third_row_seats].
+    pub fn check_heater(&self, config: &VehicleConfig) -> Result<(), TeslatteError> {
+        let supported = if self.is_front() {
+            true
+        } else if self.is_third_row() {
+            config.rear_seat_heaters > 0 && config.third_row_seats != "None"
+        } else {
+            config.rear_seat_heaters > 0
+        };
+        self.supported(supported, "heating")
+    }
+
+    /// Check the vehicle has cooling in this seat, using [VehicleConfig::has_seat_cooling]. Only
+    /// the front seats are cooled.
+    pub fn check_cooler(&self, config: &VehicleConfig) -> Result<(), TeslatteError> {
+        self.supported(self.is_front() && config.has_seat_cooling, "cooling")
+    }
+
+    fn supported(&self, supported: bool, feature: &'static str) -> Result<(), TeslatteError> {
+        if supported {
+            Ok(())
+        } else {
+            Err(TeslatteError::SeatFeatureNotSupported {
+                seat: *self,
+                feature,
+            })
+        }
+    }
+}
+
+impl Serialize for Seat {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(self.id())
+    }
+}
+
+/// A front seat, for auto seat climate, which is only available for the front seats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+pub enum FrontSeat {
+    #[strum(serialize = "front_left")]
+    Left,
+    #[strum(serialize = "front_right")]
+    Right,
+}
+
+impl From<FrontSeat> for Seat {
+    fn from(seat: FrontSeat) -> Self {
+        match seat {
+            FrontSeat::Left => Seat::FrontLeft,
+            FrontSeat::Right => Seat::FrontRight,
+        }
+    }
+}
+
+/// Auto seat climate numbers the front seats from 1.
+impl Serialize for FrontSeat {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            FrontSeat::Left => serializer.serialize_u8(1),
+            FrontSeat::Right => serializer.serialize_u8(2),
+        }
+    }
+}
+
+/// Level of a seat heater or seat cooler. The steering wheel heater uses
+/// [SteeringWheelHeatLevel].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum ClimateLevel {
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+impl Serialize for ClimateLevel {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// Level of the steering wheel heater, which has no `medium`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum SteeringWheelHeatLevel {
+    Off = 0,
+    Low = 1,
+    High = 3,
+}
+
+impl Serialize for SteeringWheelHeatLevel {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// remote_seat_heater_request
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct RemoteSeatHeater {
+    /// e.g. `front_left`, `rear_center` or `third_row_right`.
+    pub heater: Seat,
+
+    /// `off`, `low`, `medium` or `high`.
+    pub level: ClimateLevel,
+}
+
+/// remote_seat_cooler_request
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct RemoteSeatCooler {
+    /// `front_left` or `front_right`.
+    pub seat_position: Seat,
+
+    /// `off`, `low`, `medium` or `high`.
+    pub seat_cooler_level: ClimateLevel,
+}
+
+/// remote_auto_seat_climate_request
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct RemoteAutoSeatClimate {
+    /// `front_left` or `front_right`.
+    pub auto_seat_position: FrontSeat,
+
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub auto_climate_on: bool,
+}
+
+/// remote_steering_wheel_heater_request
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct RemoteSteeringWheelHeater {
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub on: bool,
+}
+
+/// remote_steering_wheel_heat_level_request
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct RemoteSteeringWheelHeatLevel {
+    /// `off`, `low` or `high`.
+    pub level: SteeringWheelHeatLevel,
+}
+
+/// remote_auto_steering_wheel_heat_climate_request
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct RemoteAutoSteeringWheelHeat {
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub on: bool,
+}
+
+/// Climate keeper mode, as reported in [ClimateState::climate_keeper_mode], e.g.
+/// `"dog".parse::<ClimateKeeperMode>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum ClimateKeeperMode {
+    Off,
+    /// Reported as `on`.
+    #[strum(serialize = "keep", serialize = "on")]
+    Keep,
+    Dog,
+    Camp,
+}
+
+impl Serialize for ClimateKeeperMode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// set_climate_keeper_mode
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SetClimateKeeperMode {
+    /// `off`, `keep`, `dog` or `camp`.
+    pub climate_keeper_mode: ClimateKeeperMode,
+}
+
+/// Cabin overheat protection, as reported in [ClimateState::cabin_overheat_protection].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum CabinOverheatProtection {
+    Off,
+    On,
+    FanOnly,
+}
+
+/// set_cabin_overheat_protection
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SetCabinOverheatProtection {
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub on: bool,
+
+    /// Only run the fan, without air conditioning.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub fan_only: bool,
+}
+
+impl From<CabinOverheatProtection> for SetCabinOverheatProtection {
+    fn from(mode: CabinOverheatProtection) -> Self {
+        Self {
+            on: mode != CabinOverheatProtection::Off,
+            fan_only: mode == CabinOverheatProtection::FanOnly,
+        }
+    }
+}
+
+/// Temperature at which cabin overheat protection starts, as reported in
+/// [ClimateState::cop_activation_temperature].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum CopActivationTemperature {
+    Low,
+    Medium,
+    High,
+}
+
+impl Serialize for CopActivationTemperature {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// set_cop_temp
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SetCopTemp {
+    /// `low`, `medium` or `high`.
+    pub cop_temp: CopActivationTemperature,
+}
+
+/// set_bioweapon_mode
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SetBioweaponMode {
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub on: bool,
+
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub manual_override: bool,
+}
+
+/// set_preconditioning_max, also known as max defrost.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SetPreconditioningMax {
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub on: bool,
+
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub manual_override: bool,
+}
+
 /// set_scheduled_charging
 #[derive(Debug, Serialize)]
 #[cfg_attr(feature = "cli", derive(clap::Args))]
@@ -534,50 +1259,1401 @@ pub struct SetScheduledDeparture {
     pub end_off_peak_time: Option<u64>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{PrintResponses, RequestData};
-
-    #[test]
-    fn vehicle_query() {
-        let url = "A/{}/B";
+/// Days of the week a schedule is enabled, as a bit mask starting with Sunday.
+///
+/// Vehicle data reports it as a number, while the commands take the day names, e.g.
+/// `"Monday,Friday"`, `"Weekdays"` or `"All"`.
+///
+/// ```rust
+/// # use teslatte::vehicles::DaysOfWeek;
+/// # use chrono::Weekday;
+/// let days: DaysOfWeek = "Weekdays".parse().unwrap();
+/// assert_eq!(days, DaysOfWeek::WEEKDAYS);
+/// assert!(days.contains(Weekday::Mon));
+/// assert!(!days.contains(Weekday::Sun));
+/// assert_eq!(days.to_string(), "Monday,Tuesday,Wednesday,Thursday,Friday");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DaysOfWeek(pub u8);
+
+impl DaysOfWeek {
+    pub const ALL: DaysOfWeek = DaysOfWeek(0b111_1111);
+    pub const WEEKDAYS: DaysOfWeek = DaysOfWeek(0b011_1110);
+
+    const NAMES: [&'static str; 7] = [
+        "Sunday",
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+    ];
+
+    pub fn contains(&self, day: chrono::Weekday) -> bool {
+        self.0 & (1 << day.num_days_from_sunday()) != 0
+    }
+}
 
-        let get_vehicle_data = GetVehicleData::new(123);
-        assert_eq!(get_vehicle_data.format(url), "A/123/B");
+impl std::fmt::Display for DaysOfWeek {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = Self::NAMES
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.0 & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+        write!(f, "{}", names.join(","))
+    }
+}
 
-        let get_vehicle_data = GetVehicleData::new_with_endpoints(
-            123,
-            vec![Endpoint::ChargeState, Endpoint::ClimateState],
-        );
-        assert_eq!(
-            get_vehicle_data.format(url),
-            "A/123/B?endpoints=charge_state;climate_state"
-        );
+impl FromStr for DaysOfWeek {
+    type Err = TeslatteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut days = 0;
+        for day in s.split(',').map(str::trim) {
+            if day.eq_ignore_ascii_case("all") {
+                days |= Self::ALL.0;
+            } else if day.eq_ignore_ascii_case("weekdays") {
+                days |= Self::WEEKDAYS.0;
+            } else {
+                let weekday: chrono::Weekday = day
+                    .parse()
+                    .map_err(|_| TeslatteError::DecodeDaysOfWeekError(s.to_string()))?;
+                days |= 1 << weekday.num_days_from_sunday();
+            }
+        }
+        Ok(Self(days))
     }
+}
 
-    #[test]
-    fn json_charge_state() {
-        let s = r#"
-    {
-      "response": {
-        "battery_heater_on": false,
-        "battery_level": 50,
-        "battery_range": 176.08,
-        "charge_amps": 5,
-        "charge_current_request": 5,
-        "charge_current_request_max": 16,
-        "charge_enable_request": true,
-        "charge_energy_added": 1.05,
-        "charge_limit_soc": 75,
-        "charge_limit_soc_max": 100,
-        "charge_limit_soc_min": 50,
-        "charge_limit_soc_std": 90,
-        "charge_miles_added_ideal": 5,
-        "charge_miles_added_rated": 5,
-        "charge_port_cold_weather_mode": false,
-        "charge_port_color": "<invalid>",
-        "charge_port_door_open": true,
+fn serialize_day_names<S>(days: &DaysOfWeek, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_str(days)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargeScheduleData {
+    pub charge_schedules: Vec<ChargeSchedule>,
+    pub max_num_charge_schedules: Option<i64>,
+    pub next_schedule: Option<bool>,
+    pub timestamp: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargeSchedule {
+    pub id: i64,
+    pub name: Option<String>,
+    pub days_of_week: DaysOfWeek,
+    pub enabled: bool,
+    pub start_enabled: bool,
+    /// Minutes after midnight (local time).
+    pub start_time: i64,
+    pub end_enabled: bool,
+    /// Minutes after midnight (local time).
+    pub end_time: i64,
+    pub one_time: bool,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreconditioningScheduleData {
+    pub precondition_schedules: Vec<PreconditionSchedule>,
+    pub max_num_precondition_schedules: Option<i64>,
+    pub next_schedule: Option<bool>,
+    pub timestamp: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreconditionSchedule {
+    pub id: i64,
+    pub name: Option<String>,
+    pub days_of_week: DaysOfWeek,
+    pub enabled: bool,
+    /// Minutes after midnight (local time) the vehicle should be ready.
+    pub precondition_time: i64,
+    pub one_time: bool,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// add_charge_schedule
+///
+/// Adds a schedule, or updates the schedule with the same `id`.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct AddChargeSchedule {
+    /// `All`, `Weekdays` or a comma separated list of days, e.g. `Monday,Friday`.
+    #[serde(serialize_with = "serialize_day_names")]
+    pub days_of_week: DaysOfWeek,
+
+    /// Whether the schedule is enabled.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub enabled: bool,
+
+    /// Minutes after midnight (local time) to start charging. If omitted, charging starts when
+    /// plugged in.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    #[serde(flatten, serialize_with = "serialize_start_time")]
+    pub start_time: Option<u32>,
+
+    /// Minutes after midnight (local time) to stop charging. If omitted, charging stops at the
+    /// charge limit.
+    #[cfg_attr(feature = "cli", clap(long))]
+    #[serde(flatten, serialize_with = "serialize_end_time")]
+    pub end_time: Option<u32>,
+
+    /// Only run the schedule once.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub one_time: bool,
+
+    /// Latitude of the location the schedule applies to.
+    #[cfg_attr(feature = "cli", clap(long, allow_negative_numbers = true))]
+    pub lat: f64,
+
+    /// Longitude of the location the schedule applies to.
+    #[cfg_attr(feature = "cli", clap(long, allow_negative_numbers = true))]
+    pub lon: f64,
+
+    /// ID of the schedule to update. A new schedule is added if omitted.
+    #[cfg_attr(feature = "cli", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+}
+
+/// `start_time` is sent along with `start_enabled`, which is whether it is set.
+fn serialize_start_time<S>(time: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serialize_enabled_time("start_enabled", "start_time", time, serializer)
+}
+
+/// `end_time` is sent along with `end_enabled`, which is whether it is set.
+fn serialize_end_time<S>(time: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serialize_enabled_time("end_enabled", "end_time", time, serializer)
+}
+
+fn serialize_enabled_time<S>(
+    enabled_key: &str,
+    time_key: &str,
+    time: &Option<u32>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::SerializeMap;
+
+    let mut map = serializer.serialize_map(Some(2))?;
+    map.serialize_entry(enabled_key, &time.is_some())?;
+    map.serialize_entry(time_key, &time.unwrap_or(0))?;
+    map.end()
+}
+
+/// add_precondition_schedule
+///
+/// Adds a schedule, or updates the schedule with the same `id`.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct AddPreconditionSchedule {
+    /// `All`, `Weekdays` or a comma separated list of days, e.g. `Monday,Friday`.
+    #[serde(serialize_with = "serialize_day_names")]
+    pub days_of_week: DaysOfWeek,
+
+    /// Whether the schedule is enabled.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub enabled: bool,
+
+    /// Minutes after midnight (local time) the vehicle should be ready.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub precondition_time: u32,
+
+    /// Only run the schedule once.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub one_time: bool,
+
+    /// Latitude of the location the schedule applies to.
+    #[cfg_attr(feature = "cli", clap(long, allow_negative_numbers = true))]
+    pub lat: f64,
+
+    /// Longitude of the location the schedule applies to.
+    #[cfg_attr(feature = "cli", clap(long, allow_negative_numbers = true))]
+    pub lon: f64,
+
+    /// ID of the schedule to update. A new schedule is added if omitted.
+    #[cfg_attr(feature = "cli", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+}
+
+/// remove_charge_schedule and remove_precondition_schedule
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct RemoveSchedule {
+    pub id: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Trunk {
+    Front,
+    Rear,
+}
+
+/// actuate_trunk
+///
+/// Opens the front trunk. Opens or closes the rear trunk, if it is powered.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct ActuateTrunk {
+    /// `front` or `rear`
+    pub which_trunk: Trunk,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum WindowCommand {
+    Vent,
+    Close,
+}
+
+/// window_control
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct WindowControl {
+    /// `vent` or `close`
+    pub command: WindowCommand,
+
+    /// Latitude of the vehicle. Required by the vehicle to close the windows. If omitted, it is
+    /// fetched from the vehicle first.
+    #[cfg_attr(feature = "cli", clap(long, allow_negative_numbers = true))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+
+    /// Longitude of the vehicle. See `lat`.
+    #[cfg_attr(feature = "cli", clap(long, allow_negative_numbers = true))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lon: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum SunRoofState {
+    Vent,
+    Close,
+    Stop,
+}
+
+/// sun_roof_control
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SunRoofControl {
+    /// `vent`, `close` or `stop`
+    pub state: SunRoofState,
+}
+
+/// A 4 digit PIN, e.g. for valet mode or PIN to drive.
+///
+/// Like tokens, it is redacted when printed.
+#[derive(Clone, PartialEq, Eq, Serialize)]
+pub struct Pin(String);
+
+redacted_fmt!(Pin);
+
+impl Pin {
+    pub fn new(pin: &str) -> Result<Self, TeslatteError> {
+        if pin.len() == 4 && pin.chars().all(|c| c.is_ascii_digit()) {
+            Ok(Self(pin.to_string()))
+        } else {
+            Err(TeslatteError::InvalidPin)
+        }
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Pin {
+    type Err = TeslatteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+/// set_sentry_mode
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SetSentryMode {
+    /// Whether sentry mode is on.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub on: bool,
+}
+
+/// set_valet_mode
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SetValetMode {
+    /// Whether valet mode is on.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub on: bool,
+
+    /// PIN to turn valet mode off again. Can be omitted if a PIN was already set.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<Pin>,
+}
+
+/// set_pin_to_drive
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SetPinToDrive {
+    /// Whether a PIN is needed to drive.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub on: bool,
+
+    /// The PIN needed to drive.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<Pin>,
+}
+
+/// speed_limit_activate, speed_limit_deactivate and speed_limit_clear_pin
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SpeedLimitPin {
+    /// The speed limit mode PIN.
+    pub pin: Pin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum SpeedUnit {
+    Mph,
+    Kph,
+}
+
+impl SpeedUnit {
+    const KPH_PER_MPH: f64 = 1.609344;
+
+    /// From [GuiSettings::gui_distance_units], e.g. `km/hr` or `mi/hr`.
+    pub fn from_gui_distance_units(units: &str) -> Self {
+        if units.starts_with("km") {
+            SpeedUnit::Kph
+        } else {
+            SpeedUnit::Mph
+        }
+    }
+
+    pub fn to_mph(&self, speed: f64) -> f64 {
+        match self {
+            SpeedUnit::Mph => speed,
+            SpeedUnit::Kph => speed / Self::KPH_PER_MPH,
+        }
+    }
+}
+
+/// speed_limit_set_limit
+///
+/// Only [SpeedLimitSetLimit::new()] creates it, so the limit is always within the range of the
+/// vehicle.
+#[derive(Debug, Serialize)]
+pub struct SpeedLimitSetLimit {
+    limit_mph: f64,
+}
+
+impl SpeedLimitSetLimit {
+    /// Convert the limit to mph, and check it is within the range the vehicle allows, from the
+    /// last known [SpeedLimitMode].
+    ///
+    /// ```rust
+    /// # use teslatte::vehicles::{SpeedLimitMode, SpeedLimitSetLimit, SpeedUnit};
+    /// let mode = SpeedLimitMode {
+    ///     active: false,
+    ///     current_limit_mph: 85.0,
+    ///     max_limit_mph: 120,
+    ///     min_limit_mph: 50.0,
+    ///     pin_code_set: false,
+    /// };
+    /// assert!(SpeedLimitSetLimit::new(100.0, SpeedUnit::Kph, &mode).is_ok());
+    /// assert!(SpeedLimitSetLimit::new(50.0, SpeedUnit::Kph, &mode).is_err());
+    /// ```
+    pub fn new(
+        limit: f64,
+        unit: SpeedUnit,
+        speed_limit_mode: &SpeedLimitMode,
+    ) -> Result<Self, TeslatteError> {
+        let limit_mph = unit.to_mph(limit);
+        let min_mph = speed_limit_mode.min_limit_mph;
+        let max_mph = speed_limit_mode.max_limit_mph as f64;
+        if limit_mph < min_mph || limit_mph > max_mph {
+            return Err(TeslatteError::SpeedLimitOutOfRange {
+                limit_mph,
+                min_mph,
+                max_mph,
+            });
+        }
+        Ok(Self { limit_mph })
+    }
+
+    pub fn limit_mph(&self) -> f64 {
+        self.limit_mph
+    }
+}
+
+/// adjust_volume
+///
+/// Only [AdjustVolume::new()] creates it, so the volume is always within the range of the
+/// vehicle.
+#[derive(Debug, Serialize)]
+pub struct AdjustVolume {
+    volume: f64,
+}
+
+impl AdjustVolume {
+    /// The highest volume the API documents.
+    pub const MAX: f64 = 11.0;
+
+    /// Check the volume is within the range of the vehicle, from the last known [MediaInfo].
+    ///
+    /// When the vehicle didn't report `audio_volume_max`, [AdjustVolume::MAX] is used.
+    ///
+    /// ```rust
+    /// # use teslatte::vehicles::{AdjustVolume, MediaInfo};
+    /// let media_info: MediaInfo =
+    ///     serde_json::from_str(r#"{"audio_volume": 2.0, "audio_volume_max": 10.333333}"#).unwrap();
+    /// assert!(AdjustVolume::new(5.0, &media_info).is_ok());
+    /// assert!(AdjustVolume::new(11.0, &media_info).is_err());
+    /// ```
+    pub fn new(volume: f64, media_info: &MediaInfo) -> Result<Self, TeslatteError> {
+        let max = media_info.audio_volume_max.unwrap_or(Self::MAX);
+        if !(0.0..=max).contains(&volume) {
+            return Err(TeslatteError::VolumeOutOfRange { volume, max });
+        }
+        Ok(Self { volume })
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+}
+
+/// navigation_request
+///
+/// Shares an address, or any text the vehicle can search for, as if it was shared from a phone.
+#[derive(Debug, Clone, Serialize)]
+pub struct NavigationRequest {
+    #[serde(rename = "type")]
+    pub share_type: String,
+    pub value: NavigationShareValue,
+    pub locale: String,
+    pub timestamp_ms: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NavigationShareValue {
+    #[serde(rename = "android.intent.extra.TEXT")]
+    pub text: String,
+}
+
+impl NavigationRequest {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            share_type: "share_ext_content_raw".to_string(),
+            value: NavigationShareValue { text: text.into() },
+            locale: "en-US".to_string(),
+            timestamp_ms: chrono::Utc::now().timestamp_millis().to_string(),
+        }
+    }
+}
+
+/// navigation_gps_request
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct NavigationGpsRequest {
+    #[cfg_attr(feature = "cli", clap(allow_negative_numbers = true))]
+    pub lat: f64,
+
+    #[cfg_attr(feature = "cli", clap(allow_negative_numbers = true))]
+    pub lon: f64,
+
+    /// Position of the destination in the route.
+    #[cfg_attr(feature = "cli", clap(short, long, default_value = "0"))]
+    pub order: u32,
+}
+
+/// navigation_sc_request
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct NavigationScRequest {
+    /// Supercharger ID, e.g. from the nearby charging sites.
+    pub id: i64,
+
+    /// Position of the Supercharger in the route.
+    #[cfg_attr(feature = "cli", clap(short, long, default_value = "0"))]
+    pub order: u32,
+}
+
+/// navigation_waypoints_request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NavigationWaypointsRequest {
+    /// Comma separated Google Maps place IDs, each prefixed with `refId:`.
+    pub waypoints: String,
+}
+
+impl NavigationWaypointsRequest {
+    /// Route through the Google Maps place IDs in order.
+    ///
+    /// ```rust
+    /// # use teslatte::vehicles::NavigationWaypointsRequest;
+    /// let request = NavigationWaypointsRequest::new(&["ChIJa", "ChIJb"]);
+    /// assert_eq!(request.waypoints, "refId:ChIJa,refId:ChIJb");
+    /// ```
+    pub fn new(place_ids: &[impl AsRef<str>]) -> Self {
+        let waypoints = place_ids
+            .iter()
+            .map(|id| format!("refId:{}", id.as_ref()))
+            .collect::<Vec<_>>()
+            .join(",");
+        Self { waypoints }
+    }
+}
+
+/// schedule_software_update
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct ScheduleSoftwareUpdate {
+    /// Seconds from now to start installing. 0 installs now.
+    #[cfg_attr(feature = "cli", clap(default_value = "0"))]
+    pub offset_sec: u64,
+}
+
+/// trigger_homelink
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct TriggerHomelink {
+    /// Latitude of the vehicle. If omitted, it is fetched from the vehicle first.
+    #[cfg_attr(feature = "cli", clap(long, allow_negative_numbers = true))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+
+    /// Longitude of the vehicle. See `lat`.
+    #[cfg_attr(feature = "cli", clap(long, allow_negative_numbers = true))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lon: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum BoomboxSound {
+    Fart = 0,
+    Ping = 2000,
+}
+
+impl Serialize for BoomboxSound {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u16(*self as u16)
+    }
+}
+
+/// remote_boombox
+///
+/// Plays a sound through the external speaker.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct RemoteBoombox {
+    /// `fart` or `ping`
+    pub sound: BoomboxSound,
+}
+
+/// set_vehicle_name
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+pub struct SetVehicleName {
+    pub vehicle_name: String,
+}
+
+/// Query for [VehicleApi::nearby_charging_sites()].
+#[derive(Debug, Clone)]
+pub struct NearbyChargingSitesValues {
+    pub vehicle_id: VehicleId,
+    /// Number of sites of each type to return.
+    pub count: Option<u32>,
+    /// Miles from the vehicle.
+    pub radius: Option<u32>,
+    /// Include more detail about each site.
+    pub detail: bool,
+}
+
+impl NearbyChargingSitesValues {
+    pub fn new(vehicle_id: impl Into<VehicleId>) -> Self {
+        Self {
+            vehicle_id: vehicle_id.into(),
+            count: None,
+            radius: None,
+            detail: false,
+        }
+    }
+}
+
+impl ApiValues for NearbyChargingSitesValues {
+    fn format(&self, url: &str) -> String {
+        let url = url.replace("{}", &format!("{}", *self.vehicle_id));
+        let mut pairs: Vec<(&str, String)> = vec![];
+        if let Some(count) = self.count {
+            pairs.push(("count", count.to_string()));
+        }
+        if let Some(radius) = self.radius {
+            pairs.push(("radius", radius.to_string()));
+        }
+        if self.detail {
+            pairs.push(("detail", "true".to_string()));
+        }
+
+        if pairs.is_empty() {
+            return url;
+        }
+        format!("{}?{}", url, join_query_pairs(&pairs))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearbyChargingSites {
+    pub congestion_sync_time_utc_secs: Option<i64>,
+    #[serde(default)]
+    pub destination_charging: Vec<DestinationCharger>,
+    #[serde(default)]
+    pub superchargers: Vec<Supercharger>,
+    pub timestamp: Option<i64>,
+}
+
+impl NearbyChargingSites {
+    /// Superchargers and destination chargers together, closest first.
+    pub fn by_distance(&self) -> Vec<NearbyChargingSite<'_>> {
+        let mut sites = self
+            .superchargers
+            .iter()
+            .map(NearbyChargingSite::Supercharger)
+            .chain(
+                self.destination_charging
+                    .iter()
+                    .map(NearbyChargingSite::Destination),
+            )
+            .collect::<Vec<_>>();
+        sites.sort_by(|a, b| a.distance_miles().total_cmp(&b.distance_miles()));
+        sites
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargingSiteLocation {
+    pub lat: f64,
+    #[serde(rename = "long")]
+    pub lon: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Supercharger {
+    /// Only returned with [NearbyChargingSitesValues::detail]. Can be used with
+    /// [NavigationScRequest].
+    pub id: Option<i64>,
+    pub name: String,
+    pub location: ChargingSiteLocation,
+    pub distance_miles: f64,
+    pub available_stalls: i64,
+    pub total_stalls: i64,
+    pub site_closed: bool,
+    pub amenities: Option<String>,
+    pub billing_info: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DestinationCharger {
+    pub name: String,
+    pub location: ChargingSiteLocation,
+    pub distance_miles: f64,
+    pub amenities: Option<String>,
+}
+
+/// Either kind of charging site in [NearbyChargingSites].
+#[derive(Debug, Clone, Copy)]
+pub enum NearbyChargingSite<'a> {
+    Supercharger(&'a Supercharger),
+    Destination(&'a DestinationCharger),
+}
+
+impl NearbyChargingSite<'_> {
+    pub fn name(&self) -> &str {
+        match self {
+            NearbyChargingSite::Supercharger(s) => &s.name,
+            NearbyChargingSite::Destination(d) => &d.name,
+        }
+    }
+
+    pub fn location(&self) -> &ChargingSiteLocation {
+        match self {
+            NearbyChargingSite::Supercharger(s) => &s.location,
+            NearbyChargingSite::Destination(d) => &d.location,
+        }
+    }
+
+    pub fn distance_miles(&self) -> f64 {
+        match self {
+            NearbyChargingSite::Supercharger(s) => s.distance_miles,
+            NearbyChargingSite::Destination(d) => d.distance_miles,
+        }
+    }
+
+    /// Whether a stall is free. Destination chargers don't report their stalls, so they are
+    /// never known to be available.
+    pub fn is_available(&self) -> bool {
+        match self {
+            NearbyChargingSite::Supercharger(s) => !s.site_closed && s.available_stalls > 0,
+            NearbyChargingSite::Destination(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PrintResponses, RequestData};
+
+    #[test]
+    fn vehicle_query() {
+        let url = "A/{}/B";
+
+        let get_vehicle_data = GetVehicleData::new(123);
+        assert_eq!(get_vehicle_data.format(url), "A/123/B");
+
+        let get_vehicle_data = GetVehicleData::new_with_endpoints(
+            123,
+            vec![Endpoint::ChargeState, Endpoint::ClimateState],
+        );
+        assert_eq!(
+            get_vehicle_data.format(url),
+            "A/123/B?endpoints=charge_state;climate_state"
+        );
+    }
+
+    #[test]
+    fn json_vehicle() {
+        let s = r#"
+    {
+      "response": {
+        "id": 1492932717794313,
+        "vehicle_id": 1449233449,
+        "vin": "5YJSA2DN0DFP21224",
+        "color": null,
+        "access_type": "OWNER",
+        "display_name": "",
+        "option_codes": null,
+        "granular_access": {
+          "hide_private": false
+        },
+        "tokens": [
+          "asdf1234"
+        ],
+        "state": "asleep",
+        "in_service": false,
+        "id_s": "1492932717794313",
+        "calendar_enabled": true,
+        "api_version": 71,
+        "backseat_token": null,
+        "backseat_token_updated_at": null
+      }
+    }
+    "#;
+
+        let request_data = RequestData::Get {
+            url: "https://owner-api.teslamotors.com/api/1/vehicles/1492932717794313",
+        };
+        let vehicle =
+            OwnerApi::parse_json::<Vehicle>(&request_data, s.to_string(), PrintResponses::Pretty)
+                .unwrap();
+        assert_eq!(vehicle.state, OnlineState::Asleep);
+    }
+
+    #[test]
+    fn window_control_payload() {
+        let vent = WindowControl {
+            command: WindowCommand::Vent,
+            lat: None,
+            lon: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&vent).unwrap(),
+            r#"{"command":"vent"}"#
+        );
+
+        let close = WindowControl {
+            command: WindowCommand::Close,
+            lat: Some(47.004162),
+            lon: Some(8.603523),
+        };
+        assert_eq!(
+            serde_json::to_string(&close).unwrap(),
+            r#"{"command":"close","lat":47.004162,"lon":8.603523}"#
+        );
+    }
+
+    #[test]
+    fn navigation_payloads() {
+        let share = NavigationRequest::new("1 Infinite Loop, Cupertino");
+        let json = serde_json::to_value(&share).unwrap();
+        assert_eq!(json["type"], "share_ext_content_raw");
+        assert_eq!(
+            json["value"]["android.intent.extra.TEXT"],
+            "1 Infinite Loop, Cupertino"
+        );
+
+        let gps = NavigationGpsRequest {
+            lat: -33.856784,
+            lon: 151.215297,
+            order: 1,
+        };
+        assert_eq!(
+            serde_json::to_string(&gps).unwrap(),
+            r#"{"lat":-33.856784,"lon":151.215297,"order":1}"#
+        );
+
+        let waypoints = NavigationWaypointsRequest::new(&["ChIJa", "ChIJb"]);
+        assert_eq!(
+            serde_json::to_string(&waypoints).unwrap(),
+            r#"{"waypoints":"refId:ChIJa,refId:ChIJb"}"#
+        );
+    }
+
+    #[test]
+    fn software_update() {
+        let s = include_str!("../testdata/vehicle_data_gak_2023_11_11.json");
+        let request_data = RequestData::Get { url: "" };
+        let software_update =
+            OwnerApi::parse_json::<VehicleData>(&request_data, s.to_string(), PrintResponses::No)
+                .unwrap()
+                .vehicle_state
+                .unwrap()
+                .software_update;
+        assert_eq!(
+            software_update.status(),
+            Some(SoftwareUpdateStatus::NoUpdate)
+        );
+        assert!(!software_update.is_pending());
+
+        let s = r#"{
+            "download_perc": 100,
+            "expected_duration_sec": 2700,
+            "install_perc": 45,
+            "status": "installing",
+            "version": "2024.26.3"
+        }"#;
+        let software_update: SoftwareUpdate = serde_json::from_str(s).unwrap();
+        assert_eq!(
+            software_update.status(),
+            Some(SoftwareUpdateStatus::Installing)
+        );
+        assert!(software_update.is_pending());
+        assert_eq!(
+            "downloading_wifi_wait".parse(),
+            Ok(SoftwareUpdateStatus::DownloadingWifiWait)
+        );
+
+        let schedule = ScheduleSoftwareUpdate { offset_sec: 7200 };
+        assert_eq!(
+            serde_json::to_string(&schedule).unwrap(),
+            r#"{"offset_sec":7200}"#
+        );
+    }
+
+    /// Stands in for the API, answering each request with the JSON `respond` returns for its
+    /// request line, e.g. `GET /api/1/vehicles/1 HTTP/1.1`.
+    async fn api_stand_in(respond: fn(&str) -> serde_json::Value) -> OwnerApi {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut api = OwnerApi::new(crate::auth::AccessToken::new("token"), None);
+        api.api_url = format!("http://{}/api/1", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let n = stream.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..n]);
+                let body = respond(request.lines().next().unwrap()).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        api
+    }
+
+    #[tokio::test]
+    async fn wait_for_software_update_times_out() {
+        // Vehicle 1 is always offline.
+        let api = api_stand_in(|request| {
+            assert!(request.starts_with("GET /api/1/vehicles/1 "));
+            serde_json::json!({
+                "response": {
+                    "id": 1,
+                    "vehicle_id": 1234,
+                    "vin": "5YJ3000000NEXUS01",
+                    "display_name": null,
+                    "state": "offline",
+                    "in_service": false
+                }
+            })
+        })
+        .await;
+
+        let result = api
+            .wait_for_software_update(
+                &VehicleId::new(1),
+                std::time::Duration::from_millis(10),
+                std::time::Duration::from_millis(100),
+                |_| panic!("an offline vehicle has no progress"),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(TeslatteError::SoftwareUpdateTimeout { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn trigger_homelink_with_location_needs_no_location_scope() {
+        let mut api = api_stand_in(|request| {
+            if request.starts_with("POST /api/1/vehicles/1/command/trigger_homelink ") {
+                return serde_json::json!({"response": {"reason": "", "result": true}});
+            }
+            assert!(
+                request.starts_with("GET /api/1/vehicles/1/vehicle_data?endpoints=vehicle_state "),
+                "{request}"
+            );
+            let s = include_str!("../testdata/vehicle_data_gak_2023_11_11.json");
+            let mut vehicle_data: serde_json::Value = serde_json::from_str(s).unwrap();
+            vehicle_data["response"]["vehicle_state"]["homelink_nearby"] = true.into();
+            vehicle_data
+        })
+        .await;
+        api.scopes = Some(vec![Scope::VehicleDeviceData, Scope::VehicleCmds]);
+
+        let homelink = TriggerHomelink {
+            lat: Some(47.004162),
+            lon: Some(8.603523),
+        };
+        api.trigger_homelink(&VehicleId::new(1), &homelink)
+            .await
+            .unwrap();
+
+        let homelink = TriggerHomelink {
+            lat: None,
+            lon: None,
+        };
+        let e = api
+            .trigger_homelink(&VehicleId::new(1), &homelink)
+            .await
+            .unwrap_err();
+        assert!(matches!(e, TeslatteError::MissingScope { .. }));
+    }
+
+    #[test]
+    fn misc_payloads() {
+        let homelink = TriggerHomelink {
+            lat: Some(47.004162),
+            lon: Some(8.603523),
+        };
+        assert_eq!(
+            serde_json::to_string(&homelink).unwrap(),
+            r#"{"lat":47.004162,"lon":8.603523}"#
+        );
+
+        let boombox = RemoteBoombox {
+            sound: BoomboxSound::Ping,
+        };
+        assert_eq!(
+            serde_json::to_string(&boombox).unwrap(),
+            r#"{"sound":2000}"#
+        );
+    }
+
+    #[test]
+    fn schedules() {
+        let s = r#"{
+            "charge_schedule_data": {
+                "charge_schedules": [
+                    {
+                        "days_of_week": 62,
+                        "enabled": true,
+                        "end_enabled": false,
+                        "end_time": 0,
+                        "id": 1700000000,
+                        "latitude": 47.004162,
+                        "longitude": 8.603523,
+                        "name": "",
+                        "one_time": false,
+                        "start_enabled": true,
+                        "start_time": 1320
+                    }
+                ],
+                "max_num_charge_schedules": 5,
+                "next_schedule": false,
+                "timestamp": 1700000000000
+            },
+            "preconditioning_schedule_data": {
+                "max_num_precondition_schedules": 10,
+                "next_schedule": false,
+                "precondition_schedules": [
+                    {
+                        "days_of_week": 127,
+                        "enabled": true,
+                        "id": 1700000001,
+                        "latitude": 47.004162,
+                        "longitude": 8.603523,
+                        "name": "Work",
+                        "one_time": true,
+                        "precondition_time": 450
+                    }
+                ],
+                "timestamp": 1700000000000
+            }
+        }"#;
+        #[derive(Deserialize)]
+        struct Schedules {
+            charge_schedule_data: ChargeScheduleData,
+            preconditioning_schedule_data: PreconditioningScheduleData,
+        }
+        let schedules: Schedules = serde_json::from_str(s).unwrap();
+        let charge = &schedules.charge_schedule_data.charge_schedules[0];
+        assert_eq!(charge.days_of_week, DaysOfWeek::WEEKDAYS);
+        assert_eq!(charge.start_time, 1320);
+        let precondition = &schedules
+            .preconditioning_schedule_data
+            .precondition_schedules[0];
+        assert_eq!(precondition.days_of_week, DaysOfWeek::ALL);
+        assert!(precondition.one_time);
+
+        assert_eq!(
+            "monday, Friday".parse::<DaysOfWeek>().unwrap(),
+            DaysOfWeek(0b010_0010)
+        );
+        assert!("Funday".parse::<DaysOfWeek>().is_err());
+
+        let add = AddChargeSchedule {
+            days_of_week: DaysOfWeek::ALL,
+            enabled: true,
+            start_time: Some(1320),
+            end_time: None,
+            one_time: false,
+            lat: 47.0,
+            lon: 8.6,
+            id: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&add).unwrap(),
+            r#"{"days_of_week":"Sunday,Monday,Tuesday,Wednesday,Thursday,Friday,Saturday","enabled":true,"start_enabled":true,"start_time":1320,"end_enabled":false,"end_time":0,"one_time":false,"lat":47.0,"lon":8.6}"#
+        );
+
+        let add = AddPreconditionSchedule {
+            days_of_week: DaysOfWeek::WEEKDAYS,
+            enabled: true,
+            precondition_time: 450,
+            one_time: false,
+            lat: 47.0,
+            lon: 8.6,
+            id: Some(1),
+        };
+        assert_eq!(
+            serde_json::to_string(&add).unwrap(),
+            r#"{"days_of_week":"Monday,Tuesday,Wednesday,Thursday,Friday","enabled":true,"precondition_time":450,"one_time":false,"lat":47.0,"lon":8.6,"id":1}"#
+        );
+    }
+
+    #[test]
+    fn json_nearby_charging_sites() {
+        let s = r#"{
+          "response": {
+            "congestion_sync_time_utc_secs": 1693588513,
+            "destination_charging": [
+              {
+                "location": { "lat": 37.409314, "long": -122.123068 },
+                "name": "Hilton Garden Inn Palo Alto",
+                "type": "destination",
+                "distance_miles": 1.35024,
+                "amenities": "restrooms,wifi,lodging"
+              }
+            ],
+            "superchargers": [
+              {
+                "location": { "lat": 37.399071, "long": -122.111216 },
+                "name": "Los Altos, CA",
+                "type": "supercharger",
+                "distance_miles": 2.111937,
+                "available_stalls": 0,
+                "total_stalls": 12,
+                "site_closed": false,
+                "amenities": "restrooms,restaurant,wifi,cafe,shopping",
+                "billing_info": ""
+              },
+              {
+                "location": { "lat": 37.441482, "long": -122.170647 },
+                "name": "Palo Alto, CA - Stanford Shopping Center",
+                "type": "supercharger",
+                "distance_miles": 0.9,
+                "available_stalls": 5,
+                "total_stalls": 8,
+                "site_closed": false,
+                "amenities": "restrooms,restaurant,wifi,cafe,shopping",
+                "billing_info": ""
+              }
+            ],
+            "timestamp": 1693588576552
+          }
+        }"#;
+        let request_data = RequestData::Get { url: "" };
+        let sites = OwnerApi::parse_json::<NearbyChargingSites>(
+            &request_data,
+            s.to_string(),
+            PrintResponses::No,
+        )
+        .unwrap();
+        assert_eq!(sites.superchargers[0].location.lon, -122.111216);
+
+        let by_distance = sites.by_distance();
+        let names = by_distance.iter().map(|s| s.name()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "Palo Alto, CA - Stanford Shopping Center",
+                "Hilton Garden Inn Palo Alto",
+                "Los Altos, CA"
+            ]
+        );
+        let available = by_distance
+            .iter()
+            .map(|s| s.is_available())
+            .collect::<Vec<_>>();
+        assert_eq!(available, [true, false, false]);
+
+        let mut values = NearbyChargingSitesValues::new(123u64);
+        assert_eq!(
+            values.format("/vehicles/{}/nearby_charging_sites"),
+            "/vehicles/123/nearby_charging_sites"
+        );
+        values.count = Some(5);
+        values.detail = true;
+        assert_eq!(
+            values.format("/vehicles/{}/nearby_charging_sites"),
+            "/vehicles/123/nearby_charging_sites?count=5&detail=true"
+        );
+    }
+
+    #[test]
+    fn pin() {
+        assert!(Pin::new("123").is_err());
+        assert!(Pin::new("12345").is_err());
+        assert!(Pin::new("12a4").is_err());
+
+        let pin = Pin::new("0123").unwrap();
+        assert_eq!(format!("{pin:?}"), r#"Pin("[REDACTED]")"#);
+
+        let valet = SetValetMode {
+            on: true,
+            password: Some(pin),
+        };
+        assert_eq!(
+            serde_json::to_string(&valet).unwrap(),
+            r#"{"on":true,"password":"0123"}"#
+        );
+    }
+
+    #[test]
+    fn speed_limit() {
+        let mode = SpeedLimitMode {
+            active: false,
+            current_limit_mph: 90.0,
+            max_limit_mph: 90,
+            min_limit_mph: 50.0,
+            pin_code_set: false,
+        };
+
+        let limit = SpeedLimitSetLimit::new(65.0, SpeedUnit::Mph, &mode).unwrap();
+        assert_eq!(limit.limit_mph(), 65.0);
+
+        let limit = SpeedLimitSetLimit::new(120.0, SpeedUnit::Kph, &mode).unwrap();
+        assert!((limit.limit_mph() - 74.56).abs() < 0.01);
+
+        assert!(SpeedLimitSetLimit::new(91.0, SpeedUnit::Mph, &mode).is_err());
+        assert!(SpeedLimitSetLimit::new(50.0, SpeedUnit::Kph, &mode).is_err());
+        assert_eq!(SpeedUnit::from_gui_distance_units("km/hr"), SpeedUnit::Kph);
+    }
+
+    #[test]
+    fn seats() {
+        let heater = RemoteSeatHeater {
+            heater: Seat::RearCenter,
+            level: ClimateLevel::High,
+        };
+        assert_eq!(
+            serde_json::to_string(&heater).unwrap(),
+            r#"{"heater":4,"level":3}"#
+        );
+
+        let auto = RemoteAutoSeatClimate {
+            auto_seat_position: "front_right".parse().unwrap(),
+            auto_climate_on: true,
+        };
+        assert_eq!(
+            serde_json::to_string(&auto).unwrap(),
+            r#"{"auto_seat_position":2,"auto_climate_on":true}"#
+        );
+        assert!("rear_left".parse::<FrontSeat>().is_err());
+        assert_eq!(Seat::from(FrontSeat::Left), Seat::FrontLeft);
+
+        let s = include_str!("../testdata/vehicle_data_HTLC_2023_10_19.json");
+        let request_data = RequestData::Get { url: "" };
+        let config =
+            OwnerApi::parse_json::<VehicleData>(&request_data, s.to_string(), PrintResponses::No)
+                .unwrap()
+                .vehicle_config
+                .unwrap();
+        assert!(Seat::RearLeft.check_heater(&config).is_ok());
+        assert!(Seat::ThirdRowLeft.check_heater(&config).is_err());
+        assert!(Seat::FrontLeft.check_cooler(&config).is_err());
+    }
+
+    #[test]
+    fn steering_wheel_heat_level() {
+        let level = RemoteSteeringWheelHeatLevel {
+            level: "high".parse().unwrap(),
+        };
+        assert_eq!(serde_json::to_string(&level).unwrap(), r#"{"level":3}"#);
+        let level = RemoteSteeringWheelHeatLevel {
+            level: SteeringWheelHeatLevel::Low,
+        };
+        assert_eq!(serde_json::to_string(&level).unwrap(), r#"{"level":1}"#);
+        assert!("medium".parse::<SteeringWheelHeatLevel>().is_err());
+    }
+
+    #[test]
+    fn climate_modes() {
+        let s = include_str!("../testdata/vehicle_data_gak_2023_11_11.json");
+        let request_data = RequestData::Get { url: "" };
+        let climate_state =
+            OwnerApi::parse_json::<VehicleData>(&request_data, s.to_string(), PrintResponses::No)
+                .unwrap()
+                .climate_state
+                .unwrap();
+
+        assert_eq!(
+            climate_state.climate_keeper_mode.parse(),
+            Ok(ClimateKeeperMode::Off)
+        );
+        assert_eq!(
+            climate_state.cabin_overheat_protection.parse(),
+            Ok(CabinOverheatProtection::On)
+        );
+        assert_eq!(
+            climate_state.cop_activation_temperature.unwrap().parse(),
+            Ok(CopActivationTemperature::High)
+        );
+        assert_eq!("on".parse(), Ok(ClimateKeeperMode::Keep));
+        assert_eq!("FanOnly".parse(), Ok(CabinOverheatProtection::FanOnly));
+
+        let mode = SetClimateKeeperMode {
+            climate_keeper_mode: ClimateKeeperMode::Dog,
+        };
+        assert_eq!(
+            serde_json::to_string(&mode).unwrap(),
+            r#"{"climate_keeper_mode":2}"#
+        );
+    }
+
+    #[test]
+    fn media_info() {
+        let s = include_str!("../testdata/vehicle_data_gak_2023_11_11.json");
+        let request_data = RequestData::Get { url: "" };
+        let media_info =
+            OwnerApi::parse_json::<VehicleData>(&request_data, s.to_string(), PrintResponses::No)
+                .unwrap()
+                .vehicle_state
+                .unwrap()
+                .media_info
+                .unwrap();
+        assert_eq!(media_info.audio_volume, Some(2.0));
+        assert_eq!(media_info.now_playing_title, None);
+        assert!(AdjustVolume::new(10.333333, &media_info).is_ok());
+        assert!(AdjustVolume::new(10.5, &media_info).is_err());
+        assert!(AdjustVolume::new(-1.0, &media_info).is_err());
+        assert!(AdjustVolume::new(f64::NAN, &media_info).is_err());
+
+        let s = r#"{
+            "a2dp_source_name": "Pixel 6",
+            "audio_volume": 2.3333,
+            "audio_volume_increment": 0.333333,
+            "audio_volume_max": 10.333333,
+            "media_playback_status": "Playing",
+            "now_playing_album": "Abbey Road",
+            "now_playing_artist": "The Beatles",
+            "now_playing_duration": 259000,
+            "now_playing_elapsed": 12000,
+            "now_playing_source": "Spotify",
+            "now_playing_station": "",
+            "now_playing_title": "Come Together"
+        }"#;
+        let media_info: MediaInfo = serde_json::from_str(s).unwrap();
+        assert_eq!(media_info.media_playback_status.as_deref(), Some("Playing"));
+        assert_eq!(
+            media_info.now_playing_title.as_deref(),
+            Some("Come Together")
+        );
+        assert_eq!(media_info.now_playing_duration, Some(259000));
+
+        let media_info: MediaInfo = serde_json::from_str("{}").unwrap();
+        assert!(AdjustVolume::new(AdjustVolume::MAX, &media_info).is_ok());
+        assert!(AdjustVolume::new(11.5, &media_info).is_err());
+    }
+
+    #[test]
+    fn json_charge_state() {
+        let s = r#"
+    {
+      "response": {
+        "battery_heater_on": false,
+        "battery_level": 50,
+        "battery_range": 176.08,
+        "charge_amps": 5,
+        "charge_current_request": 5,
+        "charge_current_request_max": 16,
+        "charge_enable_request": true,
+        "charge_energy_added": 1.05,
+        "charge_limit_soc": 75,
+        "charge_limit_soc_max": 100,
+        "charge_limit_soc_min": 50,
+        "charge_limit_soc_std": 90,
+        "charge_miles_added_ideal": 5,
+        "charge_miles_added_rated": 5,
+        "charge_port_cold_weather_mode": false,
+        "charge_port_color": "<invalid>",
+        "charge_port_door_open": true,
         "charge_port_latch": "Engaged",
         "charge_rate": 14.8,
         "charge_to_max_range": false,
@@ -909,6 +2985,32 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn vehicles_from_products_ulr_2024_08_14() {
+        let s = include_str!("../testdata/products_ulr_2024_08_14.json");
+
+        let request_data = RequestData::Get { url: "" };
+        let products = OwnerApi::parse_json::<Vec<Product>>(
+            &request_data,
+            s.to_string(),
+            PrintResponses::Pretty,
+        )
+        .unwrap();
+
+        let vehicles = vehicles_from_products(&products);
+        assert_eq!(vehicles.len(), 2);
+        assert_eq!(vehicles[0].vin, "5YJSA2DN0DFP21224");
+        assert_eq!(vehicles[0].state, OnlineState::Asleep);
+        // The energy site is skipped.
+        let s = include_str!("../testdata/products_gak_2024_04_12.json");
+        let products =
+            OwnerApi::parse_json::<Vec<Product>>(&request_data, s.to_string(), PrintResponses::No)
+                .unwrap();
+        let vehicles = vehicles_from_products(&products);
+        assert_eq!(vehicles.len(), 1);
+        assert_eq!(vehicles[0].state, OnlineState::Online);
+    }
+
     #[test]
     fn json_vehicle_data_gak_2023_11_11() {
         let s = include_str!("../testdata/vehicle_data_gak_2023_11_11.json");
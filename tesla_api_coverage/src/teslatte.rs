@@ -4,8 +4,9 @@ use crate::nom_help::{ignore_whitespace, quoted_string, short_trace};
 use crate::Restful;
 use heck::ToKebabCase;
 use nom::branch::alt;
-use nom::bytes::complete::{tag, take_while1};
+use nom::bytes::complete::{tag, take_until, take_while1};
 use nom::combinator::opt;
+use nom::sequence::delimited;
 use nom::IResult;
 use reqwest::Method;
 use std::collections::HashMap;
@@ -65,15 +66,15 @@ pub fn parse(path: &Path) -> anyhow::Result<HashMap<String, TeslatteEndpoint>> {
 /// Examples
 ///
 /// impl VehicleApi for OwnerApi {
-///     get!(vehicles, Vec<Vehicle>, "/vehicles");
-///     get_arg!(vehicle_data, VehicleData, "/vehicles/{}/vehicle_data", VehicleId);
-///     post_arg_empty!(wake_up, "/vehicles/{}/command/wake_up", VehicleId);
+///     get!(vehicles, Vec<Vehicle>, "/vehicles", []);
+///     get_arg!(vehicle_data, VehicleData, "/vehicles/{}/vehicle_data", VehicleId, [VehicleDeviceData]);
+///     post_arg_empty!(wake_up, "/vehicles/{}/command/wake_up", VehicleId, [VehicleDeviceData]);
 ///
 /// Another one:
 ///
 /// impl OwnerApi {
-///     pub_get_arg!(powerwall_status, PowerwallStatus, "/powerwalls/{}/status", PowerwallId);
-///     pub_get_args!(powerwall_energy_history, PowerwallEnergyHistory, "/powerwalls/{}/energyhistory", PowerwallEnergyHistoryValues);
+///     pub_get_arg!(powerwall_status, PowerwallStatus, "/powerwalls/{}/status", PowerwallId, [EnergyDeviceData]);
+///     pub_get_args!(powerwall_energy_history, PowerwallEnergyHistory, "/powerwalls/{}/energyhistory", PowerwallEnergyHistoryValues, [EnergyDeviceData]);
 /// }
 ///
 fn parse_file(path: &PathBuf) -> anyhow::Result<Vec<TeslatteEndpoint>> {
@@ -147,6 +148,8 @@ fn get(s: &str) -> IResult<&str, TeslatteEndpoint> {
     let (s, response_type) = struct_name(s)?;
     let (s, ()) = comma(s)?;
     let (s, uri) = quoted_string(s)?;
+    let (s, _) = opt(scopes)(s)?;
+    let (s, _) = opt(prepare)(s)?;
     let (s, _) = end_args(s)?;
 
     let endpoint = TeslatteEndpoint {
@@ -169,6 +172,8 @@ fn get_arg(s: &str) -> IResult<&str, TeslatteEndpoint> {
     let (s, uri) = quoted_string(s)?;
     let (s, ()) = comma(s)?;
     let (s, arg_type) = struct_name(s)?;
+    let (s, _) = opt(scopes)(s)?;
+    let (s, _) = opt(prepare)(s)?;
     let (s, _) = end_args(s)?;
 
     let endpoint = TeslatteEndpoint {
@@ -191,6 +196,8 @@ fn get_args(s: &str) -> IResult<&str, TeslatteEndpoint> {
     let (s, uri) = quoted_string(s)?;
     let (s, ()) = comma(s)?;
     let (s, arg_type) = struct_name(s)?;
+    let (s, _) = opt(scopes)(s)?;
+    let (s, _) = opt(prepare)(s)?;
     let (s, _) = end_args(s)?;
 
     let endpoint = TeslatteEndpoint {
@@ -213,6 +220,8 @@ fn post_arg(s: &str) -> IResult<&str, TeslatteEndpoint> {
     let (s, uri) = quoted_string(s)?;
     let (s, ()) = comma(s)?;
     let (s, arg_type) = struct_name(s)?;
+    let (s, _) = opt(scopes)(s)?;
+    let (s, _) = opt(prepare)(s)?;
     let (s, _) = end_args(s)?;
 
     let endpoint = TeslatteEndpoint {
@@ -234,6 +243,8 @@ fn post_arg_empty(s: &str) -> IResult<&str, TeslatteEndpoint> {
     let (s, uri) = quoted_string(s)?;
     let (s, ()) = comma(s)?;
     let (s, arg_type) = struct_name(s)?;
+    let (s, _) = opt(scopes)(s)?;
+    let (s, _) = opt(prepare)(s)?;
     let (s, _) = end_args(s)?;
 
     let endpoint = TeslatteEndpoint {
@@ -278,6 +289,18 @@ fn comma(s: &str) -> IResult<&str, ()> {
     Ok((s, ()))
 }
 
+// , [VehicleChargingCmds, VehicleCmds]
+fn scopes(s: &str) -> IResult<&str, &str> {
+    let (s, ()) = comma(s)?;
+    delimited(tag("["), take_until("]"), tag("]"))(s)
+}
+
+// , Self::prepare_vehicle_data
+fn prepare(s: &str) -> IResult<&str, &str> {
+    let (s, ()) = comma(s)?;
+    take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == ':')(s)
+}
+
 fn end_args(s: &str) -> IResult<&str, ()> {
     let (s, _) = tag(");")(s)?;
     Ok((s, ()))
@@ -318,6 +341,13 @@ mod tests {
         let (_, endpoint) = get_args(s).unwrap();
     }
 
+    #[test]
+    fn test_get_args_with_prepare() {
+        let s = r#"get_args!(vehicle_data, VehicleData, "/vehicles/{}/vehicle_data", GetVehicleData, [VehicleDeviceData], Self::prepare_vehicle_data);"#;
+        let (_, endpoint) = get_args(s).unwrap();
+        assert_eq!(endpoint.name, "vehicle_data");
+    }
+
     #[test]
     fn test_pub_get_args() {
         let s = r#"pub_get_args!(powerwall_energy_history, PowerwallEnergyHistory, "/powerwalls/{}/energyhistory", PowerwallEnergyHistoryValues);"#;
@@ -331,6 +361,13 @@ mod tests {
         let (_, endpoint) = post_arg(s).unwrap();
     }
 
+    #[test]
+    fn test_post_arg_with_scopes() {
+        let s = r#"post_arg!(set_charge_limit, SetChargeLimit, "/vehicles/{}/command/set_charge_limit", VehicleId, [VehicleChargingCmds, VehicleCmds]);"#;
+        let (_, endpoint) = post_arg(s).unwrap();
+        assert_eq!(endpoint.name, "set_charge_limit");
+    }
+
     #[test]
     fn test_post_arg_empty() {
         let s = r#"post_arg_empty!(wake_up, "/vehicles/{}/command/wake_up", VehicleId);"#;